@@ -45,7 +45,7 @@ pub fn main() -> anyhow::Result<()> {
 
     let now = std::time::Instant::now();
     interpreter.run_session(&session)?;
-    output.wait(ffi::MapType::MAP_TENSOR_READ, true);
+    output.wait(TensorMapType::Read, true);
     println!("run time: {:?}", now.elapsed());
 
     let now = std::time::Instant::now();
@@ -30,6 +30,33 @@ pub trait MnnToNdarrayMut {
     ) -> Result<ndarray::ArrayViewMut<Self::H, D>, MnnBridge>;
 }
 
+/// Copy a host tensor into an owned array
+pub trait MnnToNdarrayOwned {
+    type H: mnn::HalideType;
+    /// Copy the tensor into an [`ndarray::Array`] with the same shape
+    ///
+    /// The host data is assumed to be row-major in the order of [`mnn::Tensor::shape`] which
+    /// holds for [`mnn::DimensionType::Caffe`] and [`mnn::DimensionType::TensorFlow`], so this
+    /// fails for [`mnn::DimensionType::CaffeC4`] tensors
+    fn to_ndarray<D: Dimension>(&self) -> Result<ndarray::Array<Self::H, D>, MnnBridge>;
+}
+
+/// Copy an array of any layout into an owned host tensor
+pub trait NdarrayToMnnOwned {
+    type H: mnn::HalideType;
+    /// Create a tensor with the shape of the array and a copy of its elements
+    ///
+    /// The elements are copied in logical (row-major) order, so non standard layout views (e.g.
+    /// transposed or sliced arrays) are supported as well.
+    ///
+    /// Fails for arrays with more than 4 dimensions since mnn tensors have at most 4, and for
+    /// [`mnn::DimensionType::CaffeC4`] since its packed layout has no row-major equivalent
+    fn to_mnn_tensor(
+        &self,
+        dm_type: mnn::DimensionType,
+    ) -> Result<mnn::Tensor<mnn::Host<Self::H>>, MnnBridge>;
+}
+
 pub trait NdarrayToMnn {
     type H: mnn::HalideType;
     fn as_mnn_tensor(&self) -> Option<mnn::Tensor<mnn::Ref<mnn::Host<Self::H>>>>;
@@ -89,6 +116,70 @@ const _: () = {
         }
     }
 
+    impl<T> MnnToNdarrayOwned for mnn::Tensor<T>
+    where
+        T: mnn::TensorType + mnn::HostTensorType,
+        T::H: mnn::HalideType + Copy,
+    {
+        type H = T::H;
+        fn to_ndarray<D: Dimension>(&self) -> Result<ndarray::Array<Self::H, D>, MnnBridge> {
+            if self.get_dimension_type() == mnn::DimensionType::CaffeC4 {
+                return Err(Report::new(MnnBridge).attach_printable(
+                    "Cannot convert a CaffeC4 tensor to a row-major array, convert it with Tensor::to_dimension_type first",
+                ));
+            }
+            let shape = self
+                .shape()
+                .as_ref()
+                .iter()
+                .map(|&dim| dim as usize)
+                .collect::<Vec<_>>();
+            let data = self.try_host().change_context(MnnBridge)?.to_vec();
+            Ok(ndarray::ArrayD::from_shape_vec(shape, data)
+                .change_context(MnnBridge)?
+                .into_dimensionality()
+                .change_context(MnnBridge)?)
+        }
+    }
+
+    impl<T, D, A> NdarrayToMnnOwned for ndarray::ArrayBase<A, D>
+    where
+        A: ndarray::Data<Elem = T>,
+        D: ndarray::Dimension,
+        T: mnn::HalideType + Copy,
+    {
+        type H = T;
+        fn to_mnn_tensor(
+            &self,
+            dm_type: mnn::DimensionType,
+        ) -> Result<mnn::Tensor<mnn::Host<Self::H>>, MnnBridge> {
+            if self.ndim() > 4 {
+                return Err(Report::new(MnnBridge).attach_printable(format!(
+                    "Cannot create a tensor from an array with {} dimensions, mnn tensors have at most 4",
+                    self.ndim()
+                )));
+            }
+            if dm_type == mnn::DimensionType::CaffeC4 {
+                return Err(Report::new(MnnBridge)
+                    .attach_printable("Cannot create a CaffeC4 tensor from a row-major array"));
+            }
+            let shape = self
+                .shape()
+                .iter()
+                .map(|&dim| i32::try_from(dim))
+                .collect::<core::result::Result<Vec<_>, _>>()
+                .change_context(MnnBridge)
+                .attach_printable_lazy(|| {
+                    format!("Array shape {:?} overflows i32", self.shape())
+                })?;
+            let data = match self.as_slice() {
+                Some(data) => std::borrow::Cow::Borrowed(data),
+                None => std::borrow::Cow::Owned(self.iter().copied().collect()),
+            };
+            Ok(mnn::Tensor::new_with_data(shape, dm_type, &data))
+        }
+    }
+
     impl<T, D, A> NdarrayToMnn for ndarray::ArrayBase<A, D>
     where
         A: ndarray::Data<Elem = T>,
@@ -97,6 +188,9 @@ const _: () = {
     {
         type H = T;
         fn as_mnn_tensor(&self) -> Option<mnn::Tensor<mnn::Ref<mnn::Host<Self::H>>>> {
+            if self.ndim() > 4 {
+                return None;
+            }
             let shape = self.shape().iter().map(|i| *i as i32).collect::<Vec<_>>();
             let data = self.as_slice()?;
             Some(mnn::Tensor::borrowed(shape, data))
@@ -111,6 +205,9 @@ const _: () = {
     {
         type H = T;
         fn as_mnn_tensor_mut(&mut self) -> Option<mnn::Tensor<mnn::RefMut<mnn::Host<Self::H>>>> {
+            if self.ndim() > 4 {
+                return None;
+            }
             let shape = self.shape().iter().map(|i| *i as i32).collect::<Vec<_>>();
             let data = self.as_slice_mut()?;
             Some(mnn::Tensor::borrowed_mut(shape, data))
@@ -147,3 +244,31 @@ pub fn test_ndarray_to_tensor_ref() {
     let t = arr.as_mnn_tensor().unwrap();
     assert_eq!(t.host(), &[64; 6]);
 }
+#[test]
+pub fn test_tensor_ndarray_round_trip() {
+    let array = ndarray::Array::from_shape_fn(IxDyn(&[1, 2, 3, 4]), |index| {
+        (index[1] * 12 + index[2] * 4 + index[3]) as f32
+    });
+    let tensor = array.to_mnn_tensor(mnn::DimensionType::Caffe).unwrap();
+    assert_eq!(tensor.shape().as_ref(), &[1, 2, 3, 4]);
+    assert_eq!(tensor.host(), array.as_slice().unwrap());
+    assert_eq!(tensor.to_ndarray::<IxDyn>().unwrap(), array);
+
+    let transposed = array.view().permuted_axes(IxDyn(&[0, 2, 3, 1]));
+    let tensor = transposed
+        .to_mnn_tensor(mnn::DimensionType::TensorFlow)
+        .unwrap();
+    assert_eq!(tensor.shape().as_ref(), &[1, 3, 4, 2]);
+    assert_eq!(tensor.to_ndarray::<IxDyn>().unwrap(), transposed);
+}
+#[test]
+pub fn test_ndarray_to_tensor_rejects_unsupported() {
+    let array = ndarray::ArrayD::<f32>::zeros(IxDyn(&[1, 3, 2, 2]));
+    assert!(array.to_mnn_tensor(mnn::DimensionType::CaffeC4).is_err());
+    let packed = mnn::Tensor::<mnn::Host<f32>>::new([1, 3, 2, 2], mnn::DimensionType::CaffeC4);
+    assert!(packed.to_ndarray::<IxDyn>().is_err());
+
+    let array = ndarray::ArrayD::<f32>::zeros(IxDyn(&[1, 2, 3, 4, 5]));
+    assert!(array.to_mnn_tensor(mnn::DimensionType::Caffe).is_err());
+    assert!(array.as_mnn_tensor().is_none());
+}
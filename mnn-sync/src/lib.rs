@@ -46,6 +46,7 @@ pub enum CallbackEnum {
     Callback(Callback),
     Unload(oneshot::Sender<Result<()>>),
     Load(oneshot::Sender<Result<()>>),
+    Reload(Interpreter, oneshot::Sender<Result<()>>),
     Status(oneshot::Sender<bool>),
     Close,
 }
@@ -157,6 +158,26 @@ impl SessionRunnerState {
         }
     }
 
+    /// Replace the interpreter keeping the current state, if a session is loaded a new one is
+    /// created from `net` before the old one is released so a failure leaves the old model in
+    /// place
+    pub fn reload(&mut self, net: Interpreter, config: &ScheduleConfig) -> Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::trace!("Reloading session");
+        match self {
+            Self::Loaded(_) => {
+                let sr = SessionRunner::create(net, config.clone())?;
+                *self = Self::Loaded(sr);
+                Ok(())
+            }
+            Self::Unloaded(_) => {
+                *self = Self::Unloaded(net);
+                Ok(())
+            }
+            Self::Poisoned => Self::poisoned(),
+        }
+    }
+
     pub fn sr(&mut self, config: &ScheduleConfig) -> Result<&mut SessionRunner> {
         match self {
             Self::Loaded(sr) => Ok(sr),
@@ -191,6 +212,10 @@ impl SessionState {
         self.sr.unload()
     }
 
+    pub fn reload(&mut self, net: Interpreter) -> Result<()> {
+        self.sr.reload(net, &self.config)
+    }
+
     pub fn is_loaded(&self) -> bool {
         self.sr.is_loaded()
     }
@@ -324,6 +349,12 @@ impl SessionHandle {
                             .change_context(ErrorKind::SyncError)
                             .attach_printable("Internal Error: Failed to send load message")?;
                     }
+                    CallbackEnum::Reload(net, tx) => {
+                        let res = ss.reload(net);
+                        tx.send(res)
+                            .change_context(ErrorKind::SyncError)
+                            .attach_printable("Internal Error: Failed to send reload message")?;
+                    }
 
                     CallbackEnum::Status(tx) => {
                         let res = ss.is_loaded();
@@ -454,6 +485,35 @@ impl SessionHandle {
             .attach_printable("Internal Error: Failed to recv load message")?
     }
 
+    /// Swap the model of the session with a new interpreter e.g. a new version of the model
+    ///
+    /// MNN can't replace the weights of an existing session so a new session is created from
+    /// `interpreter` with the same [ScheduleConfig] on the session thread. Callbacks sent before
+    /// the reload run on the old model and callbacks sent after it on the new one. The old session
+    /// is only released once the new one was created successfully, if creating it fails the
+    /// error is returned and the old model keeps being used.
+    pub fn reload(&self, interpreter: Interpreter) -> Result<()> {
+        self.ensure_running()?;
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(CallbackEnum::Reload(interpreter, tx))
+            .map_err(|e| Report::new(ErrorKind::SyncError).attach_printable(e.to_string()))?;
+        rx.recv()
+            .change_context(ErrorKind::SyncError)
+            .attach_printable("Internal Error: Failed to recv reload message")?
+    }
+
+    pub async fn reload_async(&self, interpreter: Interpreter) -> Result<()> {
+        self.ensure_running()?;
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(CallbackEnum::Reload(interpreter, tx))
+            .map_err(|e| Report::new(ErrorKind::SyncError).attach_printable(e.to_string()))?;
+        rx.await
+            .change_context(ErrorKind::SyncError)
+            .attach_printable("Internal Error: Failed to recv reload message")?
+    }
+
     pub fn is_loaded(&self) -> Result<bool> {
         let (tx, rx) = oneshot::channel();
         self.sender
@@ -627,3 +687,25 @@ pub fn test_load_unload() {
         .expect("Failed to run");
     session_handle.unload().expect("Failed to unload");
 }
+
+#[test]
+#[cfg_attr(feature = "tracing", tracing_test::traced_test)]
+pub fn test_reload() {
+    let interpreter = Interpreter::from_file("../tests/assets/realesr.mnn")
+        .expect("Failed to create interpreter");
+    let session_handle = SessionHandle::new(interpreter, ScheduleConfig::new())
+        .expect("Failed to create session handle");
+    session_handle.load().expect("Failed to load");
+    let interpreter = Interpreter::from_file("../tests/assets/realesr.mnn")
+        .expect("Failed to create interpreter");
+    session_handle
+        .reload(interpreter)
+        .expect("Failed to reload");
+    assert!(session_handle.is_loaded().expect("Failed to get status"));
+    session_handle
+        .run(|sr| {
+            sr.run_session()?;
+            Ok(())
+        })
+        .expect("Failed to run");
+}
@@ -72,6 +72,150 @@ void mnn_ffi_emit(const char *file, size_t line, Level level,
   }
 "#;
 
+fn env_flag(name: &str) -> bool {
+    std::env::var(name)
+        .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// A preinstalled MNN to link against instead of building the vendored sources
+///
+/// Enabled by `MNN_SYS_USE_SYSTEM=1` (or the older `MNN_SYSTEM=1`) or by setting both
+/// `MNN_LIB_DIR` and `MNN_INCLUDE_DIR`
+struct SystemMnn {
+    /// Directory containing the `MNN/` headers
+    include: PathBuf,
+    /// Directory containing libMNN, if it isn't on the default linker path
+    lib_dir: Option<PathBuf>,
+}
+
+impl SystemMnn {
+    fn from_env() -> Result<Option<Self>> {
+        let include = std::env::var_os("MNN_INCLUDE_DIR").map(PathBuf::from);
+        let lib_dir = std::env::var_os("MNN_LIB_DIR").map(PathBuf::from);
+        let requested = env_flag("MNN_SYS_USE_SYSTEM") || env_flag("MNN_SYSTEM");
+        if !requested && (include.is_none() || lib_dir.is_none()) {
+            return Ok(None);
+        }
+        let include = match include {
+            Some(include) => include,
+            None => {
+                // Fall back to the headers of the source tree
+                let source = PathBuf::from(
+                    std::env::var("MNN_SRC")
+                        .ok()
+                        .unwrap_or_else(|| VENDOR.into()),
+                );
+                ensure_vendor_exists(&source)?;
+                source.join("include")
+            }
+        };
+        if !include.join("MNN").join("Interpreter.hpp").exists() {
+            anyhow::bail!(
+                "MNN headers not found in '{}', set MNN_INCLUDE_DIR to the directory containing MNN/",
+                include.display()
+            );
+        }
+        Ok(Some(Self { include, lib_dir }))
+    }
+}
+
+/// The android NDK used to cross compile MNN and generate the bindings for android targets
+///
+/// Found from `ANDROID_NDK_HOME` (or `ANDROID_NDK_ROOT` / `NDK_HOME`), the api level defaults to
+/// 21 and can be overridden with `ANDROID_PLATFORM` (e.g. `android-28` or `28`)
+struct AndroidNdk {
+    home: PathBuf,
+    abi: &'static str,
+    api_level: u32,
+}
+
+impl AndroidNdk {
+    fn from_env() -> Result<Option<Self>> {
+        if *TARGET_OS != "android" {
+            return Ok(None);
+        }
+        let home = ["ANDROID_NDK_HOME", "ANDROID_NDK_ROOT", "NDK_HOME"]
+            .into_iter()
+            .find_map(std::env::var_os)
+            .map(PathBuf::from)
+            .context("ANDROID_NDK_HOME must be set to cross compile MNN for android")?;
+        let abi = match TARGET_ARCH.as_str() {
+            "aarch64" => "arm64-v8a",
+            "arm" => "armeabi-v7a",
+            "x86_64" => "x86_64",
+            "x86" => "x86",
+            arch => anyhow::bail!("Unsupported android architecture {arch}"),
+        };
+        let api_level = match std::env::var("ANDROID_PLATFORM") {
+            Ok(platform) => platform
+                .trim_start_matches("android-")
+                .parse()
+                .with_context(|| format!("Invalid ANDROID_PLATFORM {platform}"))?,
+            Err(_) => 21,
+        };
+        let ndk = Self {
+            home,
+            abi,
+            api_level,
+        };
+        if !ndk.toolchain_file().exists() {
+            anyhow::bail!(
+                "'{}' is not an android NDK, {} doesn't exist",
+                ndk.home.display(),
+                ndk.toolchain_file().display()
+            );
+        }
+        Ok(Some(ndk))
+    }
+
+    fn toolchain_file(&self) -> PathBuf {
+        self.home
+            .join("build")
+            .join("cmake")
+            .join("android.toolchain.cmake")
+    }
+
+    /// The sysroot of the prebuilt llvm toolchain for the host
+    fn sysroot(&self) -> Result<PathBuf> {
+        let host = std::env::var("HOST")?;
+        let host_tag = if host.contains("windows") {
+            "windows-x86_64"
+        } else if host.contains("apple") {
+            // The NDK only ships a universal darwin-x86_64 toolchain
+            "darwin-x86_64"
+        } else {
+            "linux-x86_64"
+        };
+        Ok(self
+            .home
+            .join("toolchains")
+            .join("llvm")
+            .join("prebuilt")
+            .join(host_tag)
+            .join("sysroot"))
+    }
+
+    /// The clang target triple including the api level e.g. `aarch64-linux-android21`
+    fn clang_target(&self) -> String {
+        let triple = match self.abi {
+            "arm64-v8a" => "aarch64-linux-android",
+            "armeabi-v7a" => "armv7a-linux-androideabi",
+            "x86_64" => "x86_64-linux-android",
+            _ => "i686-linux-android",
+        };
+        format!("{triple}{}", self.api_level)
+    }
+
+    /// Extra clang arguments for bindgen so it parses the headers for the android target
+    fn clang_args(&self) -> Result<Vec<String>> {
+        Ok(vec![
+            format!("--target={}", self.clang_target()),
+            format!("--sysroot={}", self.sysroot()?.display()),
+        ])
+    }
+}
+
 fn ensure_vendor_exists(vendor: impl AsRef<Path>) -> Result<()> {
     let vendor_path = vendor.as_ref();
     println!(
@@ -389,6 +533,103 @@ fn ensure_vendor_exists(vendor: impl AsRef<Path>) -> Result<()> {
     Ok(())
 }
 
+/// Apply the halide and logging patches to a copy of the MNN headers in `include`
+fn patch_headers(include: &Path) -> Result<()> {
+    use itertools::Itertools;
+    let intptr = include.join("MNN").join("HalideRuntime.h");
+    #[cfg(unix)]
+    std::fs::set_permissions(&intptr, std::fs::Permissions::from_mode(0o644))?;
+
+    let intptr_contents = std::fs::read_to_string(&intptr)?;
+    let patched = intptr_contents.lines().collect::<Vec<_>>();
+    if let Some((idx, _)) = patched
+        .iter()
+        .find_position(|line| line.contains(HALIDE_SEARCH))
+    {
+        // remove the last line and the next 3 lines
+        let patched = patched
+            .into_iter()
+            .enumerate()
+            .filter(|(c_idx, _)| !(*c_idx == idx - 1 || (idx + 1..=idx + 3).contains(c_idx)))
+            .map(|(_, c)| c)
+            .collect::<Vec<_>>();
+
+        std::fs::write(intptr, patched.join("\n"))?;
+    }
+
+    let mnn_define = include.join("MNN").join("MNNDefine.h");
+    let patched = std::fs::read_to_string(&mnn_define)?.replace(TRACING_SEARCH, TRACING_REPLACE);
+    #[cfg(unix)]
+    std::fs::set_permissions(&mnn_define, std::fs::Permissions::from_mode(0o644))?;
+    std::fs::write(mnn_define, patched)?;
+    Ok(())
+}
+
+/// Only the MNN compiled here has the patched logging macros, any other library still logs with
+/// printf so `mnn::log` never sees its messages
+fn warn_unrouted_logs(library: &str) {
+    println!(
+        "cargo:warning=Linking {library}, its logs are printed with printf and don't reach the mnn::log callbacks"
+    );
+}
+
+/// How to link libMNN from `lib_dir`, static whenever a static archive is installed there
+///
+/// Without a directory the kind is left to the linker, which picks whichever libMNN it finds
+fn mnn_link_kind(lib_dir: Option<&Path>) -> &'static str {
+    let Some(lib_dir) = lib_dir else {
+        return "";
+    };
+    let has = |name: &str| lib_dir.join(name).exists();
+    // On windows MNN.lib is the import library when MNN.dll sits next to it
+    if has("libMNN.a") || (has("MNN.lib") && !has("MNN.dll")) {
+        "static="
+    } else if has("libMNN.so") || has("libMNN.dylib") || has("MNN.dll") {
+        "dylib="
+    } else {
+        ""
+    }
+}
+
+/// Reject backend features that can't be built for the current target
+///
+/// Without this the build either fails deep inside cmake or silently produces a library without
+/// the requested backend.
+fn check_features() -> Result<()> {
+    const APPLE: &[&str] = &["macos", "ios", "tvos", "visionos"];
+    let enabled = [
+        ("vulkan", cfg!(feature = "vulkan")),
+        ("metal", cfg!(feature = "metal")),
+        ("coreml", cfg!(feature = "coreml")),
+        ("opencl", cfg!(feature = "opencl")),
+        ("opengl", cfg!(feature = "opengl")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect::<Vec<_>>();
+
+    for apple_only in ["metal", "coreml"] {
+        if enabled.contains(&apple_only) && !APPLE.contains(&TARGET_OS.as_str()) {
+            anyhow::bail!(
+                "The `{apple_only}` feature is only supported on Apple targets but the target os is `{}`. Disable the `{apple_only}` feature for this target.",
+                *TARGET_OS
+            );
+        }
+    }
+    if enabled.contains(&"metal") && enabled.contains(&"vulkan") {
+        anyhow::bail!(
+            "The `metal` and `vulkan` features can't be enabled at the same time. Enable only one of them."
+        );
+    }
+    if is_emscripten() && !enabled.is_empty() {
+        anyhow::bail!(
+            "GPU backends are not supported on emscripten but the following features are enabled: {}",
+            enabled.join(", ")
+        );
+    }
+    Ok(())
+}
+
 fn main() -> Result<()> {
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-env-changed=MNN_SRC");
@@ -396,128 +637,127 @@ fn main() -> Result<()> {
     println!("cargo:rerun-if-env-changed=MNN_COMPILE");
     println!("cargo:rerun-if-env-changed=MNN_VERSION");
     println!("cargo:rerun-if-env-changed=MNN_SYSTEM");
+    println!("cargo:rerun-if-env-changed=MNN_SYS_USE_SYSTEM");
+    println!("cargo:rerun-if-env-changed=MNN_INCLUDE_DIR");
     println!("cargo:rerun-if-env-changed=MNN_FORCE_DOWNLOAD");
+    println!("cargo:rerun-if-env-changed=MNN_SYS_PREBUILT_URL");
+    println!("cargo:rerun-if-env-changed=MNN_SYS_PREBUILT_SHA256");
+    println!("cargo:rerun-if-env-changed=ANDROID_NDK_HOME");
+    println!("cargo:rerun-if-env-changed=ANDROID_NDK_ROOT");
+    println!("cargo:rerun-if-env-changed=NDK_HOME");
+    println!("cargo:rerun-if-env-changed=ANDROID_PLATFORM");
 
-    let out_dir = PathBuf::from(std::env::var("OUT_DIR")?);
+    check_features()?;
 
-    // Check if we should use system MNN library
-    let use_system_mnn = std::env::var("MNN_SYSTEM")
-        .ok()
-        .and_then(|v| match v.as_str() {
-            "1" | "true" | "yes" => Some(true),
-            _ => None,
-        })
-        .unwrap_or(false);
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR")?);
 
-    if use_system_mnn {
-        println!("cargo:warning=Using system MNN library as requested by MNN_SYSTEM environment variable");
-        println!("cargo:rustc-link-lib=MNN");
-        // We still need the headers for binding generation
+    let (include, lib_dir) = if let Some(system) = SystemMnn::from_env()? {
+        println!(
+            "cargo:warning=Using system MNN library with headers from {}",
+            system.include.display()
+        );
+        // mnn_c and the bindings need the same patched headers as the vendored build
+        let include = out_dir.join("system-include");
+        if include.exists() {
+            std::fs::remove_dir_all(&include)?;
+        }
+        std::fs::create_dir_all(&include)?;
+        fs_extra::dir::copy(
+            system.include.join("MNN"),
+            &include,
+            &fs_extra::dir::CopyOptions::new().overwrite(true),
+        )
+        .context("Failed to copy the system MNN headers")?;
+        patch_headers(&include)?;
+        warn_unrouted_logs("a system MNN");
+        (include, system.lib_dir)
+    } else {
+        // Use source specified in MNN_SRC or default to vendor directory
         let source = PathBuf::from(
             std::env::var("MNN_SRC")
                 .ok()
                 .unwrap_or_else(|| VENDOR.into()),
         );
-        ensure_vendor_exists(&source)?;
-
-        mnn_c_build(PathBuf::from(MANIFEST_DIR).join("mnn_c"), &source)
-            .with_context(|| "Failed to build mnn_c")?;
-        mnn_c_bindgen(&source, &out_dir).with_context(|| "Failed to generate mnn_c bindings")?;
-        mnn_cpp_bindgen(&source, &out_dir)
-            .with_context(|| "Failed to generate mnn_cpp bindings")?;
-        return Ok(());
-    }
 
-    // Use source specified in MNN_SRC or default to vendor directory
-    let source = PathBuf::from(
-        std::env::var("MNN_SRC")
-            .ok()
-            .unwrap_or_else(|| VENDOR.into()),
-    );
+        ensure_vendor_exists(&source)?;
 
-    ensure_vendor_exists(&source)?;
+        let vendor = out_dir.join("vendor");
+        // std::fs::remove_dir_all(&vendor).ok();
+        if !vendor.exists() {
+            // Make sure source path exists and has a CMakeLists.txt before copying
+            let cmake_path = source.join("CMakeLists.txt");
+            if !cmake_path.exists() {
+                anyhow::bail!(
+                    "Source directory '{}' does not contain CMakeLists.txt. Cannot proceed with build.",
+                    source.display()
+                );
+            }
 
-    let vendor = out_dir.join("vendor");
-    // std::fs::remove_dir_all(&vendor).ok();
-    if !vendor.exists() {
-        // Make sure source path exists and has a CMakeLists.txt before copying
-        let cmake_path = source.join("CMakeLists.txt");
-        if !cmake_path.exists() {
-            anyhow::bail!(
-                "Source directory '{}' does not contain CMakeLists.txt. Cannot proceed with build.",
-                source.display()
+            println!(
+                "cargo:warning=Copying source to build directory: {} -> {}",
+                source.display(),
+                vendor.display()
             );
+            fs_extra::dir::copy(
+                &source,
+                &vendor,
+                &fs_extra::dir::CopyOptions::new()
+                    .overwrite(true)
+                    .copy_inside(true),
+            )
+            .context("Failed to copy vendor")?;
+
+            patch_headers(&vendor.join("include"))?;
         }
 
-        println!(
-            "cargo:warning=Copying source to build directory: {} -> {}",
-            source.display(),
-            vendor.display()
-        );
-        fs_extra::dir::copy(
-            &source,
-            &vendor,
-            &fs_extra::dir::CopyOptions::new()
-                .overwrite(true)
-                .copy_inside(true),
-        )
-        .context("Failed to copy vendor")?;
-
-        use itertools::Itertools;
-        let intptr = vendor.join("include").join("MNN").join("HalideRuntime.h");
-        #[cfg(unix)]
-        std::fs::set_permissions(&intptr, std::fs::Permissions::from_mode(0o644))?;
-
-        let intptr_contents = std::fs::read_to_string(&intptr)?;
-        let patched = intptr_contents.lines().collect::<Vec<_>>();
-        if let Some((idx, _)) = patched
-            .iter()
-            .find_position(|line| line.contains(HALIDE_SEARCH))
-        {
-            // remove the last line and the next 3 lines
-            let patched = patched
-                .into_iter()
-                .enumerate()
-                .filter(|(c_idx, _)| !(*c_idx == idx - 1 || (idx + 1..=idx + 3).contains(c_idx)))
-                .map(|(_, c)| c)
-                .collect::<Vec<_>>();
-
-            std::fs::write(intptr, patched.join("\n"))?;
+        // Verify the copied vendor directory contains CMakeLists.txt
+        if !vendor.join("CMakeLists.txt").exists() {
+            anyhow::bail!(
+                "Vendor directory in build location does not contain CMakeLists.txt. Cannot proceed with build."
+            );
         }
 
-        let mnn_define = vendor.join("include").join("MNN").join("MNNDefine.h");
-        let patched =
-            std::fs::read_to_string(&mnn_define)?.replace(TRACING_SEARCH, TRACING_REPLACE);
-        #[cfg(unix)]
-        std::fs::set_permissions(&mnn_define, std::fs::Permissions::from_mode(0o644))?;
-        std::fs::write(mnn_define, patched)?;
-    }
-
-    // Verify the copied vendor directory contains CMakeLists.txt
-    if !vendor.join("CMakeLists.txt").exists() {
-        anyhow::bail!(
-            "Vendor directory in build location does not contain CMakeLists.txt. Cannot proceed with build."
-        );
-    }
+        let lib_dir = if *MNN_COMPILE {
+            let prebuilt = match std::env::var("MNN_SYS_PREBUILT_URL") {
+                Ok(url) => {
+                    let sha256 = std::env::var("MNN_SYS_PREBUILT_SHA256").context(
+                        "MNN_SYS_PREBUILT_URL is set but MNN_SYS_PREBUILT_SHA256 is not, prebuilt downloads need a pinned checksum",
+                    )?;
+                    // A failed download or checksum is an error rather than a silent fallback to
+                    // building from source, the pinned artifact is what was asked for
+                    let lib_dir = fetch_prebuilt(&url, &sha256, &out_dir)
+                        .context("Failed to use the prebuilt MNN from MNN_SYS_PREBUILT_URL")?;
+                    warn_unrouted_logs("a prebuilt MNN");
+                    Some(lib_dir)
+                }
+                Err(_) => None,
+            };
+            let lib_dir = match prebuilt {
+                Some(lib_dir) => lib_dir,
+                None => {
+                    let install_dir = out_dir.join("mnn-install");
+                    build_cmake(&vendor, &install_dir)?;
+                    install_dir.join("lib")
+                }
+            };
+            Some(lib_dir)
+        } else if let Ok(lib_dir) = std::env::var("MNN_LIB_DIR") {
+            warn_unrouted_logs("the MNN in MNN_LIB_DIR");
+            Some(PathBuf::from(lib_dir))
+        } else {
+            panic!("MNN_LIB_DIR not set while MNN_COMPILE is false");
+        };
+        (vendor.join("include"), lib_dir)
+    };
 
-    if *MNN_COMPILE {
-        let install_dir = out_dir.join("mnn-install");
-        build_cmake(&vendor, &install_dir)?;
-        println!(
-            "cargo:rustc-link-search=native={}",
-            install_dir.join("lib").display()
-        );
-    } else if let Ok(lib_dir) = std::env::var("MNN_LIB_DIR") {
-        println!("cargo:rustc-link-search=native={}", lib_dir);
-    } else {
-        panic!("MNN_LIB_DIR not set while MNN_COMPILE is false");
+    if let Some(lib_dir) = &lib_dir {
+        println!("cargo:rustc-link-search=native={}", lib_dir.display());
     }
-
-    mnn_c_build(PathBuf::from(MANIFEST_DIR).join("mnn_c"), &vendor)
+    mnn_c_build(PathBuf::from(MANIFEST_DIR).join("mnn_c"), &include)
         .with_context(|| "Failed to build mnn_c")?;
-    mnn_c_bindgen(&vendor, &out_dir).with_context(|| "Failed to generate mnn_c bindings")?;
-    mnn_cpp_bindgen(&vendor, &out_dir).with_context(|| "Failed to generate mnn_cpp bindings")?;
-    println!("cargo:include={vendor}/include", vendor = vendor.display());
+    mnn_c_bindgen(&include, &out_dir).with_context(|| "Failed to generate mnn_c bindings")?;
+    mnn_cpp_bindgen(&include, &out_dir).with_context(|| "Failed to generate mnn_cpp bindings")?;
+    println!("cargo:include={}", include.display());
     if *TARGET_OS == "macos" {
         #[cfg(feature = "metal")]
         println!("cargo:rustc-link-lib=framework=Foundation");
@@ -550,12 +790,19 @@ fn main() -> Result<()> {
             wasm32_emscripten_libs.display()
         );
     }
-    println!("cargo:rustc-link-lib=static=MNN");
+    println!(
+        "cargo:rustc-link-lib={}MNN",
+        mnn_link_kind(lib_dir.as_deref())
+    );
+    if *TARGET_OS == "android" {
+        // MNN logs to logcat on android
+        println!("cargo:rustc-link-lib=dylib=log");
+    }
     Ok(())
 }
 
-pub fn mnn_c_bindgen(vendor: impl AsRef<Path>, out: impl AsRef<Path>) -> Result<()> {
-    let vendor = vendor.as_ref();
+pub fn mnn_c_bindgen(include: impl AsRef<Path>, out: impl AsRef<Path>) -> Result<()> {
+    let include = include.as_ref();
     let mnn_c = PathBuf::from(MANIFEST_DIR).join("mnn_c");
     mnn_c.read_dir()?.flatten().for_each(|e| {
         rerun_if_changed(e.path());
@@ -584,7 +831,8 @@ pub fn mnn_c_bindgen(vendor: impl AsRef<Path>, out: impl AsRef<Path>) -> Result<
                 builder
             }
         })
-        .clang_arg(format!("-I{}", vendor.join("include").to_string_lossy()))
+        .clang_args(android_clang_args()?)
+        .clang_arg(format!("-I{}", include.to_string_lossy()))
         .pipe(|generator| {
             HEADERS.iter().fold(generator, |gen, header| {
                 gen.header(mnn_c.join(header).to_string_lossy())
@@ -614,8 +862,8 @@ pub fn mnn_c_bindgen(vendor: impl AsRef<Path>, out: impl AsRef<Path>) -> Result<
     Ok(())
 }
 
-pub fn mnn_cpp_bindgen(vendor: impl AsRef<Path>, out: impl AsRef<Path>) -> Result<()> {
-    let vendor = vendor.as_ref();
+pub fn mnn_cpp_bindgen(include: impl AsRef<Path>, out: impl AsRef<Path>) -> Result<()> {
+    let include = include.as_ref();
     let bindings = bindgen::Builder::default()
         .clang_args(["-x", "c++"])
         .clang_args(["-std=c++14"])
@@ -623,15 +871,15 @@ pub fn mnn_cpp_bindgen(vendor: impl AsRef<Path>, out: impl AsRef<Path>) -> Resul
         .clang_arg(CxxOption::METAL.cxx())
         .clang_arg(CxxOption::COREML.cxx())
         .clang_arg(CxxOption::OPENCL.cxx())
-        .clang_arg(format!("-I{}", vendor.join("include").to_string_lossy()))
+        .clang_args(android_clang_args()?)
+        .clang_arg(format!("-I{}", include.to_string_lossy()))
         .generate_cstr(true)
         .generate_inline_functions(true)
         .size_t_is_usize(true)
         .emit_diagnostics()
         .ctypes_prefix("core::ffi")
         .header(
-            vendor
-                .join("include")
+            include
                 .join("MNN")
                 .join("Interpreter.hpp")
                 .to_string_lossy(),
@@ -642,15 +890,14 @@ pub fn mnn_cpp_bindgen(vendor: impl AsRef<Path>, out: impl AsRef<Path>) -> Resul
     Ok(())
 }
 
-pub fn mnn_c_build(path: impl AsRef<Path>, vendor: impl AsRef<Path>) -> Result<()> {
+pub fn mnn_c_build(path: impl AsRef<Path>, include: impl AsRef<Path>) -> Result<()> {
     let mnn_c = path.as_ref();
     let files = mnn_c.read_dir()?.flatten().map(|e| e.path()).filter(|e| {
         e.extension() == Some(std::ffi::OsStr::new("cpp"))
             || e.extension() == Some(std::ffi::OsStr::new("c"))
     });
-    let vendor = vendor.as_ref();
     cc::Build::new()
-        .include(vendor.join("include"))
+        .include(include.as_ref())
         .pipe(|config| {
             #[cfg(feature = "vulkan")]
             config.define("MNN_VULKAN", "1");
@@ -680,8 +927,17 @@ pub fn mnn_c_build(path: impl AsRef<Path>, vendor: impl AsRef<Path>) -> Result<(
     Ok(())
 }
 
+/// The clang arguments bindgen needs for android targets, empty for every other target
+fn android_clang_args() -> Result<Vec<String>> {
+    Ok(match AndroidNdk::from_env()? {
+        Some(ndk) => ndk.clang_args()?,
+        None => Vec::new(),
+    })
+}
+
 pub fn build_cmake(path: impl AsRef<Path>, install: impl AsRef<Path>) -> Result<()> {
     let threads = std::thread::available_parallelism()?;
+    let android = AndroidNdk::from_env()?;
     cmake::Config::new(path)
         .define("CMAKE_CXX_STANDARD", "14")
         .parallel(threads.get() as u8)
@@ -711,12 +967,91 @@ pub fn build_cmake(path: impl AsRef<Path>, install: impl AsRef<Path>) -> Result<
                     .define("CMAKE_CXX_COMPILER", "em++")
                     .target("wasm32-unknown-emscripten");
             }
+            if let Some(ndk) = &android {
+                config
+                    .define("CMAKE_TOOLCHAIN_FILE", ndk.toolchain_file())
+                    .define("ANDROID_ABI", ndk.abi)
+                    .define("ANDROID_PLATFORM", format!("android-{}", ndk.api_level))
+                    .define("ANDROID_STL", "c++_static")
+                    // The armv8.2 fp16 kernels, MNN only picks them at runtime on cpus with
+                    // support for them
+                    .define(
+                        "MNN_ARM82",
+                        if ndk.abi == "arm64-v8a" { "ON" } else { "OFF" },
+                    );
+            }
             config
         })
         .build();
     Ok(())
 }
 
+/// Downloads a prebuilt static MNN for the current target and returns the directory containing it
+///
+/// `url` may contain `{target}` and `{version}` placeholders which are replaced by the target
+/// triple and `MNN_VERSION`. The download is verified against the pinned `sha256`, a checksum
+/// fetched from the same server as the library would be no protection against a tampered
+/// artifact.
+///
+/// The library must be built from the same MNN version as the headers used for the bindings.
+#[cfg(feature = "prebuilt")]
+pub fn fetch_prebuilt(url: &str, sha256: &str, out_dir: impl AsRef<Path>) -> Result<PathBuf> {
+    let target = std::env::var("TARGET")?;
+    let version = std::env::var("MNN_VERSION").unwrap_or_else(|_| MNN_DEFAULT_VERSION.to_string());
+    let url = url
+        .replace("{target}", &target)
+        .replace("{version}", &version);
+    let lib_name = if target.contains("msvc") {
+        "MNN.lib"
+    } else {
+        "libMNN.a"
+    };
+    let lib_dir = out_dir.as_ref().join("mnn-prebuilt").join(&target);
+    let lib = lib_dir.join(lib_name);
+    std::fs::create_dir_all(&lib_dir)?;
+
+    // Also accept sha256sum style "<hash>  <file>" lines
+    let expected = sha256
+        .split_whitespace()
+        .next()
+        .context("Empty MNN_SYS_PREBUILT_SHA256")?
+        .to_ascii_lowercase();
+
+    if !lib.exists() || sha256_hex(&lib)? != expected {
+        println!("cargo:warning=Downloading prebuilt MNN for {target} from {url}");
+        download(&url, &lib)?;
+        let actual = sha256_hex(&lib)?;
+        if actual != expected {
+            std::fs::remove_file(&lib).ok();
+            anyhow::bail!("Checksum mismatch for {url}: expected {expected}, got {actual}");
+        }
+    }
+    Ok(lib_dir)
+}
+
+#[cfg(feature = "prebuilt")]
+fn download(url: &str, dest: &Path) -> Result<()> {
+    let response = ureq::get(url)
+        .call()
+        .with_context(|| format!("Failed to download {url}"))?;
+    let mut file = std::fs::File::create(dest)?;
+    std::io::copy(&mut response.into_body().into_reader(), &mut file)
+        .with_context(|| format!("Failed to download {url}"))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "prebuilt"))]
+pub fn fetch_prebuilt(url: &str, _sha256: &str, _out_dir: impl AsRef<Path>) -> Result<PathBuf> {
+    anyhow::bail!("Downloading {url} needs the prebuilt feature of mnn-sys")
+}
+
+#[cfg(feature = "prebuilt")]
+fn sha256_hex(path: &Path) -> Result<String> {
+    use sha2::Digest;
+    let data = std::fs::read(path)?;
+    Ok(format!("{:x}", sha2::Sha256::digest(data)))
+}
+
 pub fn rerun_if_changed(path: impl AsRef<Path>) {
     println!("cargo:rerun-if-changed={}", path.as_ref().display());
 }
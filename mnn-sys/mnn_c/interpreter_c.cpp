@@ -1,9 +1,12 @@
 #include "interpreter_c.h"
 #include "MNN/Interpreter.hpp"
+#include <MNN/expr/Executor.hpp>
 #include <MNN/MNNForwardType.h>
+#include <algorithm>
 #include <cstdlib>
 #include <cstring>
 #include <iostream>
+#include <iterator>
 extern "C" {
 // int rust_closure_callback_runner(void *closure, Tensor *const *tensors,
 //                                  size_t tensorCount, const char *opName);
@@ -28,7 +31,30 @@ void modelPrintIO(const char *model) {
   }
 }
 
-// const char *getVersion() { return MNN::getVersion(); }
+int getRuntimeStatus(MNNForwardType type, int *statuses) {
+  MNN::BackendConfig config;
+  auto executor = MNN::Express::Executor::newExecutor(type, config, 1);
+  if (nullptr == executor) {
+    return -1;
+  }
+  for (int i = 0; i < STATUS_COUNT; ++i) {
+    statuses[i] =
+        executor->getCurrentRuntimeStatus(static_cast<MNN::RuntimeStatus>(i));
+  }
+  return 0;
+}
+int isBackendAvailable(MNNForwardType type) {
+  // createRuntime falls back to the backup type if the backend is missing or
+  // can't be created on this device, so the runtimes are only keyed by the
+  // requested type if it's available
+  MNN::ScheduleConfig config;
+  config.type = type;
+  config.backupType = MNN_FORWARD_CPU;
+  auto runtime = MNN::Interpreter::createRuntime({config});
+  auto found = runtime.first.find(type);
+  return found != runtime.first.end() && found->second != nullptr;
+}
+const char *getVersion() { return MNN::getVersion(); }
 Interpreter *Interpreter_createFromFile(const char *file) {
   return reinterpret_cast<Interpreter *>(
       MNN::Interpreter::createFromFile(file));
@@ -169,11 +195,19 @@ void Interpreter_releaseModel(Interpreter *interpreter) {
   auto mnn_interpreter = reinterpret_cast<MNN::Interpreter *>(interpreter);
   mnn_interpreter->releaseModel();
 }
-// std::pair<const void*, size_t> Interpreter_getModelBuffer(const Interpreter*
-// interpreter) {
-//     auto mnn_interpreter = reinterpret_cast<MNN::Interpreter
-//     const*>(interpreter); return mnn_interpreter->getModelBuffer();
-// }
+const void *Interpreter_getModelBuffer(const Interpreter *interpreter,
+                                      size_t *size) {
+  auto mnn_interpreter =
+      reinterpret_cast<MNN::Interpreter const *>(interpreter);
+  auto buffer = mnn_interpreter->getModelBuffer();
+  *size = buffer.second;
+  return buffer.first;
+}
+size_t Interpreter_getModelBufferSize(const Interpreter *interpreter) {
+  auto mnn_interpreter =
+      reinterpret_cast<MNN::Interpreter const *>(interpreter);
+  return mnn_interpreter->getModelBuffer().second;
+}
 const char *Interpreter_getModelVersion(const Interpreter *interpreter) {
   auto mnn_interpreter =
       reinterpret_cast<MNN::Interpreter const *>(interpreter);
@@ -279,6 +313,28 @@ int Interpreter_getSessionInfo(Interpreter *interpreter, const Session *session,
       mnn_session, static_cast<MNN::Interpreter::SessionInfoCode>(code), ptr);
   return static_cast<int>(ret);
 }
+int Interpreter_getSessionInfoAll(Interpreter *interpreter,
+                                  const Session *session, MNNSessionInfo *info) {
+  auto mnn_interpreter = reinterpret_cast<MNN::Interpreter *>(interpreter);
+  auto mnn_session = reinterpret_cast<const MNN::Session *>(session);
+  // MNN writes one entry per pipeline without reporting how many there are
+  int backends[MNN_SESSION_INFO_MAX_BACKENDS];
+  std::fill(std::begin(backends), std::end(backends), -1);
+  auto ret = mnn_interpreter->getSessionInfo(
+                 mnn_session, MNN::Interpreter::MEMORY, &info->memory) &&
+             mnn_interpreter->getSessionInfo(
+                 mnn_session, MNN::Interpreter::FLOPS, &info->flops) &&
+             mnn_interpreter->getSessionInfo(
+                 mnn_session, MNN::Interpreter::BACKENDS, backends);
+  info->backendCount = 0;
+  for (auto backend : backends) {
+    if (backend < 0) {
+      break;
+    }
+    info->backends[info->backendCount++] = static_cast<MNNForwardType>(backend);
+  }
+  return static_cast<int>(ret);
+}
 TensorInfoArray const *
 Interpreter_getSessionOutputAll(const Interpreter *interpreter,
                                 const Session *session) {
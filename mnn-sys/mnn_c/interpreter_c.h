@@ -63,6 +63,21 @@ typedef struct {
 #endif
 
 void modelPrintIO(const char *model);
+/**
+ * @brief query all the runtime statuses of a backend on the current device.
+ * @param type      the backend to query.
+ * @param statuses  output array with STATUS_COUNT entries indexed by RuntimeStatus.
+ * @return 0 on success, -1 if the backend is not available.
+ */
+int getRuntimeStatus(MNNForwardType type, int *statuses);
+
+/**
+ * @brief check whether a backend is registered in MNN and can be created on
+ * the current device.
+ * @param type      the backend to check.
+ * @return 1 if the backend is available, 0 otherwise.
+ */
+int isBackendAvailable(MNNForwardType type);
 
 /**
  * @brief get mnn version info.
@@ -155,8 +170,9 @@ void Interpreter_resizeSession(Interpreter *interpreter, Session *session);
 void Interpreter_resizeSessionWithFlag(Interpreter *interpreter,
                                        Session *session, int needRelloc);
 void Interpreter_releaseModel(Interpreter *interpreter);
-// std::pair<const void *, size_t>
-// Interpreter_getModelBuffer(const Interpreter *interpreter);
+const void *Interpreter_getModelBuffer(const Interpreter *interpreter,
+                                      size_t *size);
+size_t Interpreter_getModelBufferSize(const Interpreter *interpreter);
 const char *Interpreter_getModelVersion(const Interpreter *interpreter);
 ErrorCode Interpreter_updateSessionToModel(Interpreter *interpreter,
                                            Session *session);
@@ -176,6 +192,21 @@ Tensor *Interpreter_getSessionOutput(Interpreter *interpreter,
                                      const Session *session, const char *name);
 int Interpreter_getSessionInfo(Interpreter *interpreter, const Session *session,
                                int code, void *ptr);
+/** Maximum number of backends reported by Interpreter_getSessionInfoAll */
+#define MNN_SESSION_INFO_MAX_BACKENDS 8
+typedef struct {
+  float memory;
+  float flops;
+  int backendCount;
+  MNNForwardType backends[MNN_SESSION_INFO_MAX_BACKENDS];
+} MNNSessionInfo;
+/**
+ * @brief query the memory, flops and backends of a session at once.
+ * @param info  output, only the first backendCount entries of backends are set.
+ * @return 1 if every query succeeded, 0 otherwise.
+ */
+int Interpreter_getSessionInfoAll(Interpreter *interpreter,
+                                  const Session *session, MNNSessionInfo *info);
 TensorInfoArray const *
 Interpreter_getSessionOutputAll(const Interpreter *interpreter,
                                 const Session *session);
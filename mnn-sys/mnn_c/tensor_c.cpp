@@ -151,6 +151,27 @@ int Tensor_setDevicePtr(Tensor *tensor, const void *devicePtr, int memoryType) {
                                                                memoryType);
 }
 
+int Tensor_reshape(Tensor *tensor, const int *shape, size_t shapeSize) {
+  auto mnn_tensor = reinterpret_cast<MNN::Tensor *>(tensor);
+  // The packed layout depends on the channel dimension so it can't be reshaped
+  // in place
+  if (shapeSize > 4 ||
+      mnn_tensor->getDimensionType() == MNN::Tensor::CAFFE_C4) {
+    return 0;
+  }
+  // Only the dimensions change, the host memory is kept as is. Device tensors
+  // are laid out by their backend so only host tensors are reshaped here
+  auto &buffer = mnn_tensor->buffer();
+  buffer.dimensions = static_cast<int>(shapeSize);
+  int stride = 1;
+  for (int i = static_cast<int>(shapeSize) - 1; i >= 0; --i) {
+    buffer.dim[i].extent = shape[i];
+    buffer.dim[i].stride = stride;
+    stride *= shape[i];
+  }
+  return 1;
+}
+
 const halide_buffer_t *Tensor_buffer(const Tensor *tensor) {
   return &reinterpret_cast<const MNN::Tensor *>(tensor)->buffer();
 }
@@ -58,6 +58,7 @@ void Tensor_unmap(Tensor *tensor, MapType mtype, DimensionType dtype,
 Tensor* Tensor_clone(const Tensor *tensor);
 int Tensor_wait(Tensor *tensor, MapType mtype, int finish);
 int Tensor_setDevicePtr(Tensor *tensor, const void *devicePtr, int memoryType);
+int Tensor_reshape(Tensor *tensor, const int *shape, size_t shapeSize);
 struct halide_type_t Tensor_getType(const Tensor *tensor);
 bool Tensor_isTypeOf(const Tensor *tensor, struct halide_type_t type);
 #ifdef __cplusplus
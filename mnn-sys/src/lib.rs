@@ -1,5 +1,6 @@
 use std::ffi::CStr;
 mod tracing;
+pub use tracing::{set_log_callback, Level as LogLevel, LogCallback};
 
 pub mod cpp {
     #![allow(non_upper_case_globals)]
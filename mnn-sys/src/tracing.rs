@@ -1,7 +1,7 @@
 // This is mostly adapted from tracing-gstreamer crate's implementation
 use once_cell::sync::OnceCell;
 use std::sync::atomic::AtomicUsize;
-use std::sync::{PoisonError, RwLock};
+use std::sync::{Mutex, PoisonError, RwLock};
 use std::{collections::BTreeMap, ffi::c_char};
 use tracing_core::{field::FieldSet, identify_callsite, Callsite, Interest, Kind, Metadata};
 
@@ -130,6 +130,18 @@ impl MnnCallsite {
     }
 }
 
+/// A callback receiving the messages MNN prints with `MNN_PRINT` / `MNN_ERROR`
+pub type LogCallback = Box<dyn FnMut(Level, &str) + Send>;
+
+static LOG_CALLBACK: Mutex<Option<LogCallback>> = Mutex::new(None);
+
+/// Set the callback receiving every message MNN logs, the messages are still emitted as tracing
+/// events as well. Returns the previous callback.
+pub fn set_log_callback(callback: Option<LogCallback>) -> Option<LogCallback> {
+    let mut guard = LOG_CALLBACK.lock().unwrap_or_else(PoisonError::into_inner);
+    core::mem::replace(&mut *guard, callback)
+}
+
 #[no_mangle]
 extern "C" fn mnn_ffi_emit(
     file: *const c_char,
@@ -138,6 +150,14 @@ extern "C" fn mnn_ffi_emit(
     message: *const c_char,
 ) {
     std::panic::catch_unwind(|| {
+        if let Some(callback) = LOG_CALLBACK
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_mut()
+        {
+            let message = unsafe { core::ffi::CStr::from_ptr(message) }.to_string_lossy();
+            callback(level, message.trim_end());
+        }
         let file: &'static str = unsafe {
             core::ffi::CStr::from_ptr(file)
                 .to_str()
@@ -40,6 +40,27 @@ impl serde::Serialize for BackendConfig {
     }
 }
 
+/// Rebuilds the config from the modes written by the [`serde::Serialize`] implementation
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BackendConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        #[derive(serde::Deserialize)]
+        struct Modes {
+            memory: MemoryMode,
+            power: PowerMode,
+            precision: PrecisionMode,
+        }
+        let modes = <Modes as serde::Deserialize>::deserialize(deserializer)?;
+        Ok(BackendConfig::new()
+            .with_memory_mode(modes.memory)
+            .with_power_mode(modes.power)
+            .with_precision_mode(modes.precision))
+    }
+}
+
 impl Clone for BackendConfig {
     fn clone(&self) -> Self {
         unsafe {
@@ -106,46 +127,69 @@ impl PowerMode {
     }
 }
 
+impl core::fmt::Display for PowerMode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.to_str())
+    }
+}
+
 impl FromStr for PowerMode {
     type Err = MNNError;
+
+    /// Parses the strings returned by [`PowerMode::to_str`] case-insensitively
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
+        match s.to_ascii_lowercase().as_str() {
             "low" => Ok(Self::Low),
             "normal" => Ok(Self::Normal),
             "high" => Ok(Self::High),
-            _ => {
-                Err(error!(ErrorKind::ParseError)
-                    .attach_printable(format!("invalid power mode: {s}")))
-            }
+            _ => Err(error!(ErrorKind::ParseError)
+                .attach_printable(format!("invalid power mode: {s}"))
+                .attach_printable("Valid power modes: low, normal, high")),
         }
     }
 }
 
+impl core::fmt::Display for MemoryMode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.to_str())
+    }
+}
+
 impl FromStr for MemoryMode {
     type Err = MNNError;
+
+    /// Parses the strings returned by [`MemoryMode::to_str`] case-insensitively
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
+        match s.to_ascii_lowercase().as_str() {
             "low" => Ok(Self::Low),
             "normal" => Ok(Self::Normal),
             "high" => Ok(Self::High),
-            _ => {
-                Err(error!(ErrorKind::ParseError)
-                    .attach_printable(format!("invalid memory mode: {s}")))
-            }
+            _ => Err(error!(ErrorKind::ParseError)
+                .attach_printable(format!("invalid memory mode: {s}"))
+                .attach_printable("Valid memory modes: low, normal, high")),
         }
     }
 }
 
+impl core::fmt::Display for PrecisionMode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.to_str())
+    }
+}
+
 impl FromStr for PrecisionMode {
     type Err = MNNError;
+
+    /// Parses the strings returned by [`PrecisionMode::to_str`] case-insensitively
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
+        match s.to_ascii_lowercase().as_str() {
             "low" => Ok(Self::Low),
             "normal" => Ok(Self::Normal),
             "high" => Ok(Self::High),
             "low_bf16" => Ok(Self::LowBf16),
             _ => Err(error!(ErrorKind::ParseError)
-                .attach_printable(format!("invalid precision mode: {s}"))),
+                .attach_printable(format!("invalid precision mode: {s}"))
+                .attach_printable("Valid precision modes: low, normal, high, low_bf16")),
         }
     }
 }
@@ -246,6 +290,29 @@ impl BackendConfig {
         }
     }
 
+    /// Create a backend config with all three modes set at once
+    pub fn new_with(power: PowerMode, precision: PrecisionMode, memory: MemoryMode) -> Self {
+        Self::new()
+            .with_power_mode(power)
+            .with_precision_mode(precision)
+            .with_memory_mode(memory)
+    }
+
+    /// Start building a backend config, the modes that aren't set keep MNN's defaults
+    ///
+    /// ```rust
+    /// # use mnn::*;
+    /// let config = BackendConfig::builder()
+    ///     .power(PowerMode::High)
+    ///     .precision(PrecisionMode::Low)
+    ///     .build();
+    /// assert_eq!(config.get_power_mode(), PowerMode::High);
+    /// assert_eq!(config.get_memory_mode(), BackendConfig::new().get_memory_mode());
+    /// ```
+    pub fn builder() -> BackendConfigBuilder {
+        BackendConfigBuilder::default()
+    }
+
     /// Sets the [MemoryMode] for the backend
     pub fn set_memory_mode(&mut self, mode: MemoryMode) {
         unsafe {
@@ -334,6 +401,146 @@ impl BackendConfig {
     }
 }
 
+/// Builder for a [`BackendConfig`] created by [`BackendConfig::builder`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackendConfigBuilder {
+    power: Option<PowerMode>,
+    precision: Option<PrecisionMode>,
+    memory: Option<MemoryMode>,
+    flags: Option<usize>,
+}
+
+impl BackendConfigBuilder {
+    /// Sets the [PowerMode] for the backend
+    pub fn power(mut self, mode: PowerMode) -> Self {
+        self.power = Some(mode);
+        self
+    }
+
+    /// Sets the [PrecisionMode] for the backend
+    pub fn precision(mut self, mode: PrecisionMode) -> Self {
+        self.precision = Some(mode);
+        self
+    }
+
+    /// Sets the [MemoryMode] for the backend
+    pub fn memory(mut self, mode: MemoryMode) -> Self {
+        self.memory = Some(mode);
+        self
+    }
+
+    /// Sets the flags for the backend, see [`BackendConfig::set_flags`]
+    pub fn flags(mut self, flags: usize) -> Self {
+        self.flags = Some(flags);
+        self
+    }
+
+    /// Create the configured [`BackendConfig`]
+    pub fn build(self) -> BackendConfig {
+        let mut config = BackendConfig::new();
+        if let Some(power) = self.power {
+            config.set_power_mode(power);
+        }
+        if let Some(precision) = self.precision {
+            config.set_precision_mode(precision);
+        }
+        if let Some(memory) = self.memory {
+            config.set_memory_mode(memory);
+        }
+        if let Some(flags) = self.flags {
+            config.set_flags(flags);
+        }
+        config
+    }
+}
+
+/// Capabilities of a backend on the current device as reported by MNN
+///
+/// A capability is `None` if it couldn't be queried, in which case it should be treated as
+/// unsupported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BackendFeatures {
+    /// Whether the backend could be created on this device
+    pub available: bool,
+    /// Supports 16-bit floating point arithmetic
+    pub fp16: Option<bool>,
+    /// Supports dot-product instructions (this is what speeds up int8 models)
+    pub dot_product: Option<bool>,
+    /// Supports running with a lower priority to save power
+    pub power_low: Option<bool>,
+}
+
+impl BackendFeatures {
+    /// Features of a backend that couldn't be queried
+    pub const UNKNOWN: Self = Self {
+        available: false,
+        fp16: None,
+        dot_product: None,
+        power_low: None,
+    };
+
+    /// Returns true if nothing is known about the backend
+    pub fn is_unknown(&self) -> bool {
+        *self == Self::UNKNOWN
+    }
+
+    /// The precision modes that are expected to work on this backend
+    ///
+    /// [PrecisionMode::Low] is only included if the backend reports fp16 support and
+    /// [PrecisionMode::LowBf16] is never included since it depends on how MNN was built
+    pub fn precision_modes(&self) -> Vec<PrecisionMode> {
+        if !self.available {
+            return Vec::new();
+        }
+        let mut modes = vec![PrecisionMode::Normal, PrecisionMode::High];
+        if self.fp16 == Some(true) {
+            modes.push(PrecisionMode::Low);
+        }
+        modes
+    }
+}
+
+/// Query the capabilities of a backend on the current device
+///
+/// This creates a runtime for the backend so it can be slow for GPU backends, cache the result if
+/// you need it more than once.
+/// Returns [BackendFeatures::UNKNOWN] if the backend isn't available
+pub fn backend_features(forward: crate::ForwardType) -> BackendFeatures {
+    // The runtime status query silently falls back to the CPU for missing backends
+    if !forward.is_available() {
+        return BackendFeatures::UNKNOWN;
+    }
+    let mut statuses = [0; RuntimeStatus::STATUS_COUNT as usize];
+    let ret = unsafe { getRuntimeStatus(forward.to_mnn_sys(), statuses.as_mut_ptr()) };
+    if ret != 0 {
+        return BackendFeatures::UNKNOWN;
+    }
+    let status = |s: RuntimeStatus| Some(statuses[s as usize] != 0);
+    BackendFeatures {
+        available: true,
+        fp16: status(RuntimeStatus::STATUS_SUPPORT_FP16),
+        dot_product: status(RuntimeStatus::STATUS_SUPPORT_DOT_PRODUCT),
+        power_low: status(RuntimeStatus::STATUS_SUPPORT_POWER_LOW),
+    }
+}
+
+#[test]
+fn test_backend_features_cpu() {
+    let features = backend_features(crate::ForwardType::CPU);
+    assert!(features.available);
+    assert!(features.precision_modes().contains(&PrecisionMode::Normal));
+}
+
+#[test]
+fn test_forward_type_available() {
+    assert!(crate::ForwardType::CPU.is_available());
+    assert!(crate::ForwardType::Auto.is_available());
+    let available = crate::ForwardType::available();
+    assert!(available.contains(&crate::ForwardType::CPU));
+    assert!(!available.contains(&crate::ForwardType::Auto));
+}
+
 #[test]
 fn test_backend_config() {
     let mut config = BackendConfig::new();
@@ -352,3 +559,28 @@ fn test_backend_config() {
     assert_eq!(config.get_power_mode(), PowerMode::Normal);
     assert_eq!(config.get_precision_mode(), PrecisionMode::Normal);
 }
+
+#[test]
+fn test_mode_string_round_trip() {
+    fn round_trip<T>(modes: &[T])
+    where
+        T: FromStr<Err = MNNError> + core::fmt::Display + core::fmt::Debug + PartialEq + Copy,
+    {
+        for &mode in modes {
+            let s = mode.to_string();
+            assert_eq!(s.parse::<T>().unwrap(), mode);
+            assert_eq!(s.to_uppercase().parse::<T>().unwrap(), mode);
+        }
+        assert!("".parse::<T>().is_err());
+        assert!("extreme".parse::<T>().is_err());
+    }
+    round_trip(&[PowerMode::Low, PowerMode::Normal, PowerMode::High]);
+    round_trip(&[MemoryMode::Low, MemoryMode::Normal, MemoryMode::High]);
+    round_trip(&[
+        PrecisionMode::Normal,
+        PrecisionMode::High,
+        PrecisionMode::Low,
+        PrecisionMode::LowBf16,
+    ]);
+    assert_eq!(PrecisionMode::LowBf16.to_string(), "low_bf16");
+}
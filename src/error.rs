@@ -50,6 +50,8 @@ pub enum ErrorKind {
     #[error("Ascii Error")]
     AsciiError,
     /// HalideType mismatch (e.g. trying to convert from a float tensor to an int tensor)
+    ///
+    /// The data type the tensor actually holds is attached to the report
     #[error("HalideType mismatch: got {got}")]
     HalideTypeMismatch {
         /// HalideType that was
@@ -67,6 +69,21 @@ pub enum ErrorKind {
     /// Tried to run a dynamic tensor without resizing it first
     #[error("Dynamic Tensor Error: Tensor needs to be resized before using")]
     DynamicTensorError,
+    /// Invalid combination of schedule / backend config options
+    #[error("Invalid Config")]
+    InvalidConfig,
+    /// The session needs more memory than the limit of its schedule config
+    #[error("Memory limit exceeded")]
+    MemoryLimitExceeded,
+    /// The model couldn't be parsed (e.g. a truncated or corrupt model file)
+    #[error("Failed to parse the model")]
+    ModelParseFailed,
+    /// The requested forward type isn't available on this device or build
+    #[error("Unsupported forward type: {0:?}")]
+    UnsupportedForwardType(crate::ForwardType),
+    /// MNN failed to create the session (e.g. an op isn't supported by the backend)
+    #[error("Failed to create the session")]
+    SessionCreationFailed,
 }
 
 impl MNNError {
@@ -3,7 +3,8 @@ use crate::tensor::list::TensorList;
 use std::{ffi::CStr, path::Path, sync::Arc};
 
 use crate::{
-    AsTensorShape, Device, RawTensor, Ref, RefMut, ScheduleConfig, Tensor, TensorType, prelude::*,
+    AsTensorShape, Device, Host, RawTensor, Ref, RefMut, ScheduleConfig, Tensor, TensorType,
+    prelude::*,
 };
 use mnn_sys::HalideType;
 
@@ -129,11 +130,50 @@ impl SessionMode {
     }
 }
 
+/// The version of the linked MNN library e.g. `3.0.0`
+pub fn version() -> &'static str {
+    unsafe { std::ffi::CStr::from_ptr(mnn_sys::getVersion()) }
+        .to_str()
+        .unwrap_or("unknown")
+}
+
+/// Make sure the cache file can be created / read and written, creating the parent directory if
+/// needed
+fn ensure_cache_file_accessible(path: &Path) -> Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent)
+            .change_context(ErrorKind::IOError)
+            .attach_printable("Failed to create the directory for the cache file")
+            .attach_printable_lazy(|| parent.display().to_string())?;
+    }
+    let exists = path.exists();
+    std::fs::OpenOptions::new()
+        .read(exists)
+        .append(true)
+        .create(!exists)
+        .open(path)
+        .change_context(ErrorKind::IOError)
+        .attach_printable("Cache file is not readable / writable")
+        .attach_printable_lazy(|| path.display().to_string())?;
+    if !exists {
+        // Don't leave an empty cache file around, MNN will create it when saving the cache
+        std::fs::remove_file(path)
+            .change_context(ErrorKind::IOError)
+            .attach_printable_lazy(|| path.display().to_string())?;
+    }
+    Ok(())
+}
+
 /// net data holder. multiple sessions could share same net.
-#[repr(transparent)]
 #[derive(Debug)]
 pub struct Interpreter {
     pub(crate) inner: *mut mnn_sys::Interpreter,
+    /// The file the model was loaded from, only used for error messages
+    pub(crate) model_path: Option<std::path::PathBuf>,
+    pub(crate) cache_file: Option<std::path::PathBuf>,
     pub(crate) __marker: PhantomData<()>,
 }
 
@@ -157,9 +197,11 @@ impl Interpreter {
         let path = path.to_str().ok_or_else(|| error!(ErrorKind::AsciiError))?;
         let c_path = std::ffi::CString::new(path).change_context(ErrorKind::AsciiError)?;
         let interpreter = unsafe { mnn_sys::Interpreter_createFromFile(c_path.as_ptr()) };
-        ensure!(!interpreter.is_null(), ErrorKind::InterpreterError; "Failed to create interpreter", "Interpreter_createFromFile returned null");
+        ensure!(!interpreter.is_null(), ErrorKind::ModelParseFailed; format!("Failed to load the model from {path}"), "Interpreter_createFromFile returned null");
         Ok(Self {
             inner: interpreter,
+            model_path: Some(std::path::PathBuf::from(path)),
+            cache_file: None,
             __marker: PhantomData,
         })
     }
@@ -169,24 +211,55 @@ impl Interpreter {
     /// `bytes`: the buffer of the model
     ///
     /// return: the created net/interpreter
+    ///
+    /// The bytes are copied so the interpreter doesn't borrow the buffer, this makes it possible
+    /// to ship a model embedded with [`include_bytes!`] without writing it to a file first.
     pub fn from_bytes(bytes: impl AsRef<[u8]>) -> Result<Self> {
         let bytes = bytes.as_ref();
         let size = bytes.len();
         let interpreter =
             unsafe { mnn_sys::Interpreter_createFromBuffer(bytes.as_ptr().cast(), size) };
-        ensure!(!interpreter.is_null(), ErrorKind::InterpreterError; "Failed to create interpreter", "Interpreter_createFromBuffer returned null");
+        ensure!(!interpreter.is_null(), ErrorKind::ModelParseFailed; format!("Failed to load the model from a buffer of {size} bytes"), "Interpreter_createFromBuffer returned null");
         Ok(Self {
             inner: interpreter,
+            model_path: None,
+            cache_file: None,
             __marker: PhantomData,
         })
     }
 
+    /// Create a new independent net/interpreter from the same model.
+    ///
+    /// The new interpreter is created from the model buffer MNN already holds, so the model file
+    /// isn't read again. Fails after [`Interpreter::release_model`] since the buffer is gone.
+    ///
+    /// Sessions created from the returned interpreter are fully independent of the sessions of
+    /// `self` and can be run concurrently with them.
+    pub fn try_clone(&self) -> Result<Self> {
+        let mut size = 0;
+        let buffer = unsafe { mnn_sys::Interpreter_getModelBuffer(self.inner, &mut size) };
+        ensure!(
+            !buffer.is_null() && size > 0,
+            ErrorKind::InterpreterError;
+            "The model buffer has been released"
+        );
+        // Interpreter_createFromBuffer copies the buffer
+        let mut net =
+            Self::from_bytes(unsafe { core::slice::from_raw_parts(buffer.cast::<u8>(), size) })?;
+        net.model_path.clone_from(&self.model_path);
+        Ok(net)
+    }
+
     /// Set session mode
     ///
     /// `mode`: the session mode
     ///
     /// **Warning:**
     /// It should be called before create session!
+    ///
+    /// The mode only applies to the sessions created after it was set, e.g. call it with
+    /// [`SessionMode::Release`] before [`Interpreter::create_session`] to skip the op callbacks or
+    /// with [`SessionMode::MemoryCache`] to keep the memory of the session across resizes.
     pub fn set_session_mode(&mut self, mode: SessionMode) {
         unsafe { mnn_sys::Interpreter_setSessionMode(self.inner, mode.to_mnn_sys()) }
     }
@@ -195,14 +268,16 @@ impl Interpreter {
     ///
     ///output tensor buffer (host or deviceId) should be retrieved after resize of any input tensor.
     ///
-    ///`session`: the session to be prepared
+    ///`session`: the session to be prepared, it must have been created by this interpreter which
+    /// isn't checked
     pub fn resize_session(&self, session: &mut crate::Session) {
         unsafe { mnn_sys::Interpreter_resizeSession(self.inner, session.inner) }
     }
 
     /// Resize session and reallocate the buffer.
     ///
-    /// `session`: the session to be prepared.
+    /// `session`: the session to be prepared, it must have been created by this interpreter which
+    /// isn't checked
     ///
     /// # Note
     /// NeedRelloc is default to 1, 1 means need realloc!
@@ -224,6 +299,22 @@ impl Interpreter {
         }
     }
 
+    /// Resize a raw tensor e.g. an input from [`Interpreter::raw_input`] using the given shape
+    ///
+    /// Call [`Interpreter::resize_session`] after resizing the inputs of a session so the
+    /// shapes of the outputs are updated before running it
+    pub fn resize_raw_tensor(&self, tensor: &mut RawTensor, dims: impl AsTensorShape) {
+        let dims = dims.as_tensor_shape();
+        unsafe {
+            mnn_sys::Interpreter_resizeTensor(
+                self.inner,
+                tensor.inner,
+                dims.shape.as_ptr(),
+                dims.size,
+            )
+        }
+    }
+
     /// Resize tensor by
     /// - N -> batch
     /// - C -> channel
@@ -254,22 +345,70 @@ impl Interpreter {
     /// `schedule` : the config of the session
     ///
     /// return: the created session
+    ///
+    /// Fails with [`ErrorKind::UnsupportedForwardType`] or [`ErrorKind::SessionCreationFailed`]
+    /// if MNN can't create the session, the model source and forward type are attached to the error.
+    ///
+    /// The memory limit of the schedule config is a post-hoc check: MNN can't estimate the memory
+    /// of a session without allocating it, so the session is created first and released again
+    /// before returning [`ErrorKind::MemoryLimitExceeded`] if its memory exceeds the limit. The
+    /// peak usage of the process can therefore briefly go over the limit.
     pub fn create_session(
         &mut self,
         schedule: crate::ScheduleConfig,
     ) -> Result<crate::session::Session> {
         profile!("Creating session"; {
+            let memory_limit = schedule.get_memory_limit_mb();
             let session = unsafe { mnn_sys::Interpreter_createSession(self.inner, schedule.inner) };
-            assert!(!session.is_null());
-            Ok(crate::session::Session {
+            if session.is_null() {
+                return Err(self.session_creation_error([schedule.get_type()]));
+            }
+            let session = crate::session::Session {
                 inner: session,
                 net: self.inner,
                 __session_internals: crate::SessionInternals::Single(schedule),
                 __marker: PhantomData,
-            })
+            };
+            if let Some(limit) = memory_limit {
+                let memory = self.memory(&session)?;
+                if memory > limit as f32 {
+                    self.release_session(session)?;
+                    return Err(error!(ErrorKind::MemoryLimitExceeded).attach_printable(format!(
+                        "The session needs {memory:.2}MB but the limit is {limit}MB"
+                    )));
+                }
+            }
+            Ok(session)
         })
     }
 
+    /// Check that `session` was created by this interpreter, passing a session to another
+    /// interpreter is undefined behaviour in MNN
+    pub(crate) fn ensure_owns(&self, session: &crate::session::Session) -> Result<()> {
+        ensure!(
+            session.net == self.inner,
+            ErrorKind::InterpreterError;
+            "The session was created by another interpreter"
+        );
+        Ok(())
+    }
+
+    /// Release a session and the memory of its backend
+    ///
+    /// The interpreter remains valid afterwards and can be used to create new sessions. The
+    /// session is consumed so it can't be used after it was released. Dropping a session releases
+    /// it as well, this only makes the teardown explicit and reports failures.
+    ///
+    /// Returns an error if the session was created by another interpreter or MNN fails to release
+    /// it
+    pub fn release_session(&mut self, mut session: crate::session::Session) -> Result<()> {
+        self.ensure_owns(&session)?;
+        let ret = unsafe { mnn_sys::Interpreter_releaseSession(self.inner, session.inner) };
+        session.inner = core::ptr::null_mut();
+        ensure!(ret != 0, ErrorKind::InterpreterError; "Failed to release the session");
+        Ok(())
+    }
+
     /// Release the model file buffer
     /// # Safety
     /// This function is marked unsafe since it's not clear what the safety guarantees are right
@@ -291,7 +430,9 @@ impl Interpreter {
             let schedules: crate::ScheduleConfigs = schedule.into_iter().collect();
             let sc: &[_] = schedules.inner.as_ref();
             let session = unsafe { mnn_sys::Interpreter_createMultiPathSession(self.inner, sc.as_ptr(), sc.len()) };
-            assert!(!session.is_null());
+            if session.is_null() {
+                return Err(self.session_creation_error(schedules.forward_types()));
+            }
             Ok(crate::session::Session {
                 inner: session,
                 net: self.inner,
@@ -301,6 +442,28 @@ impl Interpreter {
         })
     }
 
+    /// The error for a session that MNN failed to create with the given forward types
+    ///
+    /// [`ErrorKind::UnsupportedForwardType`] if one of them isn't available, otherwise
+    /// [`ErrorKind::SessionCreationFailed`]
+    fn session_creation_error(
+        &self,
+        forward_types: impl IntoIterator<Item = crate::ForwardType>,
+    ) -> MNNError {
+        let forward_types: Vec<_> = forward_types.into_iter().collect();
+        let source = match &self.model_path {
+            Some(path) => format!("Model: {}", path.display()),
+            None => format!("Model: buffer of {} bytes", self.model_buffer_size()),
+        };
+        let error = match forward_types.iter().find(|forward| !forward.is_available()) {
+            Some(&forward) => error!(ErrorKind::UnsupportedForwardType(forward)),
+            None => error!(ErrorKind::SessionCreationFailed),
+        };
+        error
+            .attach_printable(source)
+            .attach_printable(format!("Forward types: {forward_types:?}"))
+    }
+
     /// Print all input and output tensors info.
     pub fn model_print_io(path: impl AsRef<Path>) -> Result<()> {
         let path = path.as_ref();
@@ -313,7 +476,8 @@ impl Interpreter {
 
     /// Get the input tensor of the session.
     ///
-    /// `session`: the session to get input tensor
+    /// `session`: the session to get input tensor, it must have been created by this interpreter
+    /// which isn't checked
     ///
     /// return: List of input tensors
     pub fn inputs<'i>(&self, session: &'i crate::Session) -> TensorList<'i> {
@@ -333,6 +497,7 @@ impl Interpreter {
         session: &'s crate::Session,
         name: impl AsRef<str>,
     ) -> Result<Tensor<RefMut<'s, Device<H>>>> {
+        self.ensure_owns(session)?;
         let name = name.as_ref();
         let c_name = std::ffi::CString::new(name).change_context(ErrorKind::AsciiError)?;
         let input = unsafe {
@@ -347,17 +512,36 @@ impl Interpreter {
             ErrorKind::HalideTypeMismatch {
                 got: std::any::type_name::<H>(),
             };
-            format!("Input tensor \"{name}\" is not of type {}", std::any::type_name::<H>())
+            format!("Input tensor \"{name}\" is not of type {}", std::any::type_name::<H>()),
+            format!("The tensor holds {}", tensor.dtype_name())
         );
         Ok(tensor)
     }
 
+    /// Allocate a host tensor matching the shape and data type of the input tensor `name` of the
+    /// session
+    ///
+    /// Fill it and copy it to the input with [`Tensor::copy_from_host_tensor`] to avoid feeding
+    /// an input with the wrong shape or data type.
+    ///
+    /// Fails like [`Interpreter::input`] if the input doesn't exist, has a dynamic shape or isn't
+    /// of type `H`.
+    pub fn make_input_tensor<H: HalideType>(
+        &self,
+        session: &crate::Session,
+        name: impl AsRef<str>,
+    ) -> Result<Tensor<Host<H>>> {
+        let input = self.input::<H>(session, name)?;
+        Ok(input.create_host_tensor_from_device(false))
+    }
+
     /// Get the raw input tensor of a session by name
     pub fn raw_input<'s>(
         &self,
         session: &'s crate::Session,
         name: impl AsRef<str>,
     ) -> Result<RawTensor<'s>> {
+        self.ensure_owns(session)?;
         let name = name.as_ref();
         let c_name = std::ffi::CString::new(name).change_context(ErrorKind::AsciiError)?;
         let input = unsafe {
@@ -374,6 +558,7 @@ impl Interpreter {
         session: &'s crate::Session,
         name: impl AsRef<str>,
     ) -> Result<Tensor<RefMut<'s, Device<H>>>> {
+        self.ensure_owns(session)?;
         let name = name.as_ref();
         let c_name = std::ffi::CString::new(name).change_context(ErrorKind::AsciiError)?;
         let input = unsafe {
@@ -385,7 +570,8 @@ impl Interpreter {
             tensor.is_type_of::<H>(),
             ErrorKind::HalideTypeMismatch {
                 got: std::any::type_name::<H>(),
-            }
+            };
+            format!("The tensor holds {}", tensor.dtype_name())
         );
         Ok(tensor)
     }
@@ -395,7 +581,8 @@ impl Interpreter {
     /// as well as the shape of the tensor
     ///
     /// **Panics** if the name is not ascii
-    /// **Undefined Behavior** if the tensor is not of type `H`
+    /// **Undefined Behavior** if the tensor is not of type `H` or the session was created by
+    /// another interpreter
     pub unsafe fn input_unchecked<'s, H: HalideType>(
         &self,
         session: &'s crate::Session,
@@ -420,12 +607,26 @@ impl Interpreter {
         session: &'s crate::Session,
         name: impl AsRef<str>,
     ) -> Result<Tensor<Ref<'s, Device<H>>>> {
-        let name = name.as_ref();
-        let c_name = std::ffi::CString::new(name).change_context(ErrorKind::AsciiError)?;
-        let output = unsafe {
-            mnn_sys::Interpreter_getSessionOutput(self.inner, session.inner, c_name.as_ptr())
-        };
-        ensure!(!output.is_null(), ErrorKind::IOError;format!("Output tensor \"{name}\" not found"));
+        self.typed_output(session, Some(name.as_ref()))
+    }
+
+    /// Get the only output tensor of a session without knowing its name
+    ///
+    /// Returns an error if the session doesn't have exactly one output, use
+    /// [`Interpreter::output`] with the name of the tensor for models with multiple outputs.
+    pub fn single_output<'s, H: HalideType>(
+        &self,
+        session: &'s crate::Session,
+    ) -> Result<Tensor<Ref<'s, Device<H>>>> {
+        self.typed_output(session, None)
+    }
+
+    fn typed_output<'s, H: HalideType>(
+        &self,
+        session: &'s crate::Session,
+        name: Option<&str>,
+    ) -> Result<Tensor<Ref<'s, Device<H>>>> {
+        let output = self.session_output(session, name)?;
         let tensor = unsafe { Tensor::from_ptr(output) };
         let shape = tensor.shape();
         ensure!(!shape.as_ref().contains(&-1), ErrorKind::DynamicTensorError);
@@ -433,7 +634,8 @@ impl Interpreter {
             tensor.is_type_of::<H>(),
             ErrorKind::HalideTypeMismatch {
                 got: std::any::type_name::<H>(),
-            }
+            };
+            format!("The tensor holds {}", tensor.dtype_name())
         );
         Ok(tensor)
     }
@@ -444,25 +646,252 @@ impl Interpreter {
         session: &'s crate::Session,
         name: impl AsRef<str>,
     ) -> Result<RawTensor<'s>> {
-        let name = name.as_ref();
-        let c_name = std::ffi::CString::new(name).change_context(ErrorKind::AsciiError)?;
-        let output = unsafe {
-            mnn_sys::Interpreter_getSessionOutput(self.inner, session.inner, c_name.as_ptr())
-        };
-        ensure!(!output.is_null(), ErrorKind::IOError;format!("Output tensor \"{name}\" not found"));
+        let output = self.session_output(session, Some(name.as_ref()))?;
         Ok(RawTensor::from_ptr(output))
     }
 
+    /// Get the only raw output tensor of a session without knowing its name
+    ///
+    /// Returns an error if the session doesn't have exactly one output, use
+    /// [`Interpreter::raw_output`] with the name of the tensor for models with multiple outputs.
+    pub fn raw_single_output<'s>(&self, session: &'s crate::Session) -> Result<RawTensor<'s>> {
+        let output = self.session_output(session, None)?;
+        Ok(RawTensor::from_ptr(output))
+    }
+
+    /// Get the output tensor pointer by name or the only output if `name` is `None`
+    fn session_output(
+        &self,
+        session: &crate::Session,
+        name: Option<&str>,
+    ) -> Result<*mut mnn_sys::Tensor> {
+        self.ensure_owns(session)?;
+        let output = match name {
+            Some(name) => {
+                let c_name = std::ffi::CString::new(name).change_context(ErrorKind::AsciiError)?;
+                let output = unsafe {
+                    mnn_sys::Interpreter_getSessionOutput(
+                        self.inner,
+                        session.inner,
+                        c_name.as_ptr(),
+                    )
+                };
+                ensure!(!output.is_null(), ErrorKind::IOError;format!("Output tensor \"{name}\" not found"));
+                output
+            }
+            None => {
+                let count = self.outputs(session).size();
+                ensure!(
+                    count == 1,
+                    ErrorKind::TensorError;
+                    format!("Session has {count} outputs, the output name needs to be specified")
+                );
+                let output = unsafe {
+                    mnn_sys::Interpreter_getSessionOutput(
+                        self.inner,
+                        session.inner,
+                        core::ptr::null(),
+                    )
+                };
+                ensure!(!output.is_null(), ErrorKind::IOError; "Output tensor not found");
+                output
+            }
+        };
+        Ok(output)
+    }
+
+    /// The session tensor of a binding, looked up on every run since resizing the session can
+    /// replace it
+    fn bound_tensor(
+        &self,
+        session: &crate::Session,
+        binding: &crate::session::Binding,
+    ) -> Result<*mut mnn_sys::Tensor> {
+        let name =
+            std::ffi::CString::new(binding.name.as_str()).change_context(ErrorKind::AsciiError)?;
+        let name = name.as_ptr();
+        let tensor = unsafe {
+            if binding.output {
+                mnn_sys::Interpreter_getSessionOutput(self.inner, session.inner, name)
+            } else {
+                mnn_sys::Interpreter_getSessionInput(self.inner, session.inner, name)
+            }
+        };
+        ensure!(
+            !tensor.is_null(),
+            ErrorKind::TensorError;
+            format!(
+                "{} tensor {:?} not found",
+                if binding.output { "Output" } else { "Input" },
+                binding.name
+            )
+        );
+        Ok(tensor)
+    }
+
+    fn check_binding(
+        session_tensor: *mut mnn_sys::Tensor,
+        binding: &crate::session::Binding,
+    ) -> Result<()> {
+        let name = &binding.name;
+        let (target, tensor) = (
+            RawTensor::from_ptr(session_tensor),
+            RawTensor::from_ptr(binding.tensor),
+        );
+        ensure!(!target.is_dynamic_unsized(), ErrorKind::DynamicTensorError);
+        ensure!(
+            unsafe { mnn_sys::Tensor_isTypeOf(session_tensor, mnn_sys::Tensor_getType(binding.tensor)) },
+            ErrorKind::HalideTypeMismatch {
+                got: tensor.data_type().name(),
+            };
+            format!("Tensor {name:?} is not of type {}", tensor.data_type().name()),
+            format!("The tensor holds {}", target.data_type().name())
+        );
+        ensure!(
+            target.element_size() == tensor.element_size(),
+            ErrorKind::SizeMismatch {
+                expected: target.element_size(),
+                got: tensor.element_size(),
+            };
+            format!("Tensor {name:?} has shape {:?} but got {:?}", target.shape(), tensor.shape())
+        );
+        ensure!(
+            target.get_dimension_type() == tensor.get_dimension_type(),
+            ErrorKind::TensorError;
+            format!(
+                "Tensor {name:?} has dimension type {:?} but got {:?}",
+                target.get_dimension_type(),
+                tensor.get_dimension_type()
+            )
+        );
+        Ok(())
+    }
+
+    /// Copy the bound inputs (or outputs) between the user tensors and the session
+    fn copy_bindings(
+        &self,
+        session: &crate::Session,
+        bindings: &crate::Bindings<'_>,
+        output: bool,
+    ) -> Result<()> {
+        for binding in bindings.bindings.iter().filter(|b| b.output == output) {
+            let target = self.bound_tensor(session, binding)?;
+            Self::check_binding(target, binding)?;
+            let ret = unsafe {
+                if output {
+                    mnn_sys::Tensor_copyToHostTensor(target, binding.tensor)
+                } else {
+                    mnn_sys::Tensor_copyFromHostTensor(target, binding.tensor)
+                }
+            };
+            ensure!(
+                ret != 0,
+                ErrorKind::TensorCopyFailed(ret);
+                format!("Failed to copy the bound tensor {:?}", binding.name)
+            );
+        }
+        Ok(())
+    }
+
     /// Run a session
+    ///
+    /// Returns an error if the session was created by another interpreter
     pub fn run_session(&mut self, session: &crate::session::Session) -> Result<()> {
+        self.ensure_owns(session)?;
         profile!("Running session"; {
             let ret = unsafe { mnn_sys::Interpreter_runSession(self.inner, session.inner) };
             ensure!(
                 ret == mnn_sys::ErrorCode::ERROR_CODE_NO_ERROR,
                 ErrorKind::InternalError(ret)
             );
-            Ok(())
-        })
+        });
+        Ok(())
+    }
+
+    /// Run a session with user owned host tensors as its inputs and outputs
+    ///
+    /// The bound inputs are copied into the session before the run and the outputs are copied
+    /// into the bound tensors after it, so the inputs and outputs don't have to be fetched and
+    /// copied manually. Since the data is copied this works with every backend.
+    ///
+    /// Fails if the session was created by another interpreter, a bound tensor doesn't have the
+    /// same shape, data type and dimension type as the session tensor of the same name or the
+    /// session tensor doesn't exist
+    pub fn run_session_with(
+        &mut self,
+        session: &crate::session::Session,
+        bindings: &mut crate::Bindings<'_>,
+    ) -> Result<()> {
+        self.ensure_owns(session)?;
+        self.copy_bindings(session, bindings, false)?;
+        self.run_session(session)?;
+        self.copy_bindings(session, bindings, true)
+    }
+
+    /// Copy the named `inputs` into the session, run it and copy every output to a host tensor
+    ///
+    /// This is a convenience for the common case of models with `f32` inputs and outputs, for
+    /// anything else use [`Interpreter::input`] / [`Interpreter::output`] directly.
+    ///
+    /// Fails if an input is not part of the session, an input or output is not of type `f32` or
+    /// the shape of an input doesn't match the session (resize the session first for dynamic
+    /// shapes).
+    pub fn run_with(
+        &mut self,
+        session: &crate::session::Session,
+        inputs: &std::collections::HashMap<&str, Tensor<Host<f32>>>,
+    ) -> Result<std::collections::HashMap<String, Tensor<Host<f32>>>> {
+        let is_f32 = |tensor: &RawTensor| unsafe {
+            mnn_sys::Tensor_isTypeOf(tensor.inner, mnn_sys::halide_type_of::<f32>())
+        };
+        for (&name, tensor) in inputs {
+            let mut input = self.raw_input(session, name)?;
+            ensure!(!input.is_dynamic_unsized(), ErrorKind::DynamicTensorError);
+            ensure!(
+                is_f32(&input),
+                ErrorKind::HalideTypeMismatch {
+                    got: std::any::type_name::<f32>(),
+                };
+                format!("Input tensor \"{name}\" is not of type f32"),
+                format!("The tensor holds {}", input.data_type().name())
+            );
+            ensure!(
+                input.element_size() == tensor.element_size(),
+                ErrorKind::SizeMismatch {
+                    expected: input.element_size(),
+                    got: tensor.element_size(),
+                };
+                format!("Input tensor \"{name}\" has shape {:?} but got {:?}", input.shape(), tensor.shape())
+            );
+            input
+                .copy_from_host_tensor(&RawTensor::from_ptr(tensor.tensor))
+                .map_err(|e| {
+                    e.attach_printable(format!("Failed to copy input tensor \"{name}\""))
+                })?;
+        }
+        self.run_session(session)?;
+        self.output_names(session)
+            .into_iter()
+            .map(|name| {
+                let output = self.raw_output(session, &name)?;
+                ensure!(
+                    is_f32(&output),
+                    ErrorKind::HalideTypeMismatch {
+                        got: std::any::type_name::<f32>(),
+                    };
+                    format!("Output tensor \"{name}\" is not of type f32"),
+                    format!("The tensor holds {}", output.data_type().name())
+                );
+                output.wait(crate::TensorMapType::Read, true);
+                let host = Tensor::<Host<f32>>::new(output.shape(), output.get_dimension_type());
+                output
+                    .copy_to_host_tensor(&mut RawTensor::from_ptr(host.tensor))
+                    .map_err(|e| {
+                        e.attach_printable(format!("Failed to copy output tensor \"{name}\""))
+                    })?;
+                Ok((name, host))
+            })
+            .collect()
     }
 
     /// Run a session with a callback
@@ -481,6 +910,7 @@ impl Interpreter {
         end: impl Fn(&[RawTensor], OperatorInfo) -> bool + 'static,
         sync: bool,
     ) -> Result<()> {
+        self.ensure_owns(session)?;
         let sync = sync as libc::c_int;
         let before = TensorCallback::from(before).into_ptr();
         let end = TensorCallback::from(end).into_ptr();
@@ -500,13 +930,68 @@ impl Interpreter {
         Ok(())
     }
 
+    /// Run a session once and measure the time and flops of each operator
+    ///
+    /// The ops are returned in execution order. The time of an op is measured between the before
+    /// and after callbacks of [`Interpreter::run_session_with_callback`] so the session is run
+    /// synchronously. MNN only reports the ops of sessions in [`SessionMode::Debug`] (the default).
+    pub fn profile_ops(&mut self, session: &crate::session::Session) -> Result<Vec<OpProfile>> {
+        use std::{
+            cell::{Cell, RefCell},
+            rc::Rc,
+            time::Instant,
+        };
+        self.ensure_owns(session)?;
+        let start = Rc::new(Cell::new(None::<Instant>));
+        let ops = Rc::new(RefCell::new(Vec::new()));
+        let (before_start, end_start, end_ops) = (start.clone(), start, ops.clone());
+        self.run_session_with_callback(
+            session,
+            move |_, _| {
+                before_start.set(Some(Instant::now()));
+                true
+            },
+            move |_, op| {
+                if let Some(start) = end_start.take() {
+                    end_ops.borrow_mut().push(OpProfile {
+                        name: op.name().to_string_lossy().into_owned(),
+                        op_type: op.type_name().to_string_lossy().into_owned(),
+                        flops: op.flops(),
+                        time: start.elapsed(),
+                    });
+                }
+                true
+            },
+            true,
+        )?;
+        Ok(core::mem::take(&mut *ops.borrow_mut()))
+    }
+
     /// Get all output tensors of a session
+    ///
+    /// The session must have been created by this interpreter which isn't checked
     pub fn outputs<'o>(&self, session: &'o crate::session::Session) -> TensorList<'o> {
         let outputs =
             unsafe { mnn_sys::Interpreter_getSessionOutputAll(self.inner, session.inner) };
         TensorList::from_ptr(outputs)
     }
 
+    /// The names of all the input tensors of a session
+    pub fn input_names(&self, session: &crate::session::Session) -> Vec<String> {
+        self.inputs(session)
+            .iter()
+            .map(|input| input.name().to_owned())
+            .collect()
+    }
+
+    /// The names of all the output tensors of a session
+    pub fn output_names(&self, session: &crate::session::Session) -> Vec<String> {
+        self.outputs(session)
+            .iter()
+            .map(|output| output.name().to_owned())
+            .collect()
+    }
+
     /// If the cache exist, try to load cache from file.
     /// After createSession, try to save cache to file.
     ///
@@ -514,6 +999,10 @@ impl Interpreter {
     ///
     /// `key_size` : the size of key
     ///
+    /// The parent directory of `cache_file` is created if it doesn't exist.
+    /// Returns an error if the cache file can't be read or written, since MNN would otherwise
+    /// silently skip the cache.
+    ///
     /// # Note
     /// The API should be called before create session.
     ///
@@ -521,31 +1010,162 @@ impl Interpreter {
     pub fn set_cache_file(&mut self, path: impl AsRef<Path>, key_size: usize) -> Result<()> {
         let path = path.as_ref();
         let path = dunce::simplified(path);
-        let path = path.to_str().ok_or_else(|| error!(ErrorKind::AsciiError))?;
-        let c_path = std::ffi::CString::new(path).change_context(ErrorKind::AsciiError)?;
+        ensure_cache_file_accessible(path)?;
+        let c_path = path.to_str().ok_or_else(|| error!(ErrorKind::AsciiError))?;
+        let c_path = std::ffi::CString::new(c_path).change_context(ErrorKind::AsciiError)?;
         unsafe { mnn_sys::Interpreter_setCacheFile(self.inner, c_path.as_ptr(), key_size) }
+        self.cache_file = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Set the file holding the external weights of the model
+    ///
+    /// Large models keep their weights in a separate file (usually `<model>.mnn.weight`) that's
+    /// referenced by the graph. The weights are read from it when a session is created.
+    ///
+    /// Returns an error if `path` doesn't exist or isn't valid utf-8.
+    ///
+    /// # Note
+    /// The API should be called before [`Interpreter::create_session`], sessions that already
+    /// exist keep the weights they were created with.
+    pub fn set_external_file(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let path = dunce::simplified(path);
+        ensure!(
+            path.is_file(),
+            ErrorKind::IOError;
+            format!("External weight file {} doesn't exist", path.display())
+        );
+        let c_path = path.to_str().ok_or_else(|| error!(ErrorKind::AsciiError))?;
+        let c_path = std::ffi::CString::new(c_path).change_context(ErrorKind::AsciiError)?;
+        // The flag is the size of the buffer used to read the weights, 128 is MNN's default
+        unsafe { mnn_sys::Interpreter_setExternalFile(self.inner, c_path.as_ptr(), 128) }
         Ok(())
     }
 
     /// Update cache file
-    pub fn update_cache_file(&mut self, session: &mut crate::session::Session) -> Result<()> {
-        MNNError::from_error_code(unsafe {
-            mnn_sys::Interpreter_updateCacheFile(self.inner, session.inner)
-        });
+    ///
+    /// Returns whether the cache file was written or changed by the update, i.e. `false` if the
+    /// cache loaded with [`Interpreter::set_cache_file`] was already up to date or no cache file
+    /// was set.
+    ///
+    /// Returns an error if MNN fails to update the cache or if the written cache file can't be
+    /// read back
+    pub fn update_cache_file(&mut self, session: &mut crate::session::Session) -> Result<bool> {
+        self.ensure_owns(session)?;
+        let before = self.cache_file_state();
+        let ret = unsafe { mnn_sys::Interpreter_updateCacheFile(self.inner, session.inner) };
+        if ret != mnn_sys::ErrorCode::ERROR_CODE_NO_ERROR {
+            return Err(MNNError::from_error_code(ret));
+        }
+        if let Some(path) = self.cache_file.as_deref().filter(|path| path.exists()) {
+            std::fs::File::open(path)
+                .change_context(ErrorKind::IOError)
+                .attach_printable("Failed to read back the cache file")
+                .attach_printable_lazy(|| path.display().to_string())?;
+        }
+        let after = self.cache_file_state();
+        Ok(after.is_some() && after != before)
+    }
+
+    /// The size in bytes of the cache file set with [`Interpreter::set_cache_file`]
+    ///
+    /// Returns `None` if no cache file was set or it hasn't been written yet
+    pub fn cache_file_size(&self) -> Option<u64> {
+        self.cache_file_state().map(|(size, _)| size)
+    }
+
+    /// The size and modification time of the cache file if it exists
+    fn cache_file_state(&self) -> Option<(u64, Option<std::time::SystemTime>)> {
+        let metadata = self.cache_file.as_deref()?.metadata().ok()?;
+        Some((metadata.len(), metadata.modified().ok()))
+    }
+
+    /// Compile the kernels of the backend for a config and write them to a cache file without
+    /// running inference
+    ///
+    /// This creates a session with `sc` purely to populate the cache at `cache_path` and releases
+    /// it afterwards. Use it to prepare the cache of GPU backends (e.g. OpenCL) ahead of time and
+    /// ship it with the model so the first run doesn't pay the compilation cost, load it with
+    /// [`Interpreter::set_cache_file`].
+    ///
+    /// # Note
+    /// This calls [`Interpreter::set_cache_file`] with `cache_path` and MNN has no way to unset
+    /// the cache file, so every session created by this interpreter afterwards reads and writes
+    /// the cache at `cache_path`. Use a separate interpreter to precompile if that's not wanted.
+    pub fn precompile(
+        &mut self,
+        sc: crate::ScheduleConfig,
+        cache_path: impl AsRef<Path>,
+    ) -> Result<()> {
+        self.set_cache_file(cache_path, 128)?;
+        let mut session = self.create_session(sc)?;
+        self.update_cache_file(&mut session)?;
         Ok(())
     }
 
     /// Wait for all output tensors to be ready after computation
     pub fn wait(&self, session: &crate::session::Session) {
         self.outputs(session).iter().for_each(|tinfo| {
-            tinfo
-                .raw_tensor()
-                .wait(mnn_sys::MapType::MAP_TENSOR_READ, true);
+            tinfo.raw_tensor().wait(crate::TensorMapType::Read, true);
         });
     }
 
+    /// Estimate the memory needed by a session with the given config in MB without running it
+    ///
+    /// This creates a session with `sc`, queries its [`Interpreter::memory`] and releases it right
+    /// away so it can be used to decide whether a model fits in the available memory before
+    /// committing to it. The memory limit of `sc` isn't enforced since the estimate is what it
+    /// would be checked against.
+    pub fn estimate_memory(&mut self, sc: &crate::ScheduleConfig) -> Result<f32> {
+        let mut sc = sc.clone();
+        sc.memory_limit_mb = None;
+        let session = self.create_session(sc)?;
+        let memory = self.memory(&session);
+        self.release_session(session)?;
+        memory
+    }
+
+    /// Size of the model buffer held by the interpreter in bytes
+    ///
+    /// Returns 0 after [`Interpreter::release_model`]
+    pub fn model_buffer_size(&self) -> usize {
+        unsafe { mnn_sys::Interpreter_getModelBufferSize(self.inner) }
+    }
+
+    /// The metadata embedded in the model header
+    ///
+    /// MNN's interpreter only exposes the following fields of the header, missing or empty fields
+    /// are left out:
+    /// - `bizCode`: the business code set when converting the model
+    /// - `uuid`: the unique id of the model
+    /// - `version`: the version of the converter that produced the model (`<2.0.0` for models
+    ///   converted before the version was recorded)
+    ///
+    /// The `Extra` info of a model is only accessible through MNN's express module API and not
+    /// included. The model must not have been released with [`Interpreter::release_model`].
+    pub fn metadata(&self) -> std::collections::BTreeMap<String, String> {
+        let fields = unsafe {
+            [
+                ("bizCode", mnn_sys::Interpreter_bizCode(self.inner)),
+                ("uuid", mnn_sys::Interpreter_uuid(self.inner)),
+                ("version", mnn_sys::Interpreter_getModelVersion(self.inner)),
+            ]
+        };
+        fields
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| {
+                let value = unsafe { CStr::from_ptr(value) }.to_string_lossy();
+                (key.to_owned(), value.into_owned())
+            })
+            .filter(|(_, value)| !value.is_empty())
+            .collect()
+    }
+
     /// Get memory usage of a session in MB
     pub fn memory(&self, session: &crate::session::Session) -> Result<f32> {
+        self.ensure_owns(session)?;
         let mut memory = 0f32;
         let memory_ptr = &mut memory as *mut f32;
         let ret = unsafe {
@@ -566,6 +1186,7 @@ impl Interpreter {
 
     /// Get float operation needed in session in M
     pub fn flops(&self, session: &crate::Session) -> Result<f32> {
+        self.ensure_owns(session)?;
         let mut flop = 0.0f32;
         let flop_ptr = &mut flop as *mut f32;
         let ret = unsafe {
@@ -584,8 +1205,43 @@ impl Interpreter {
         Ok(flop)
     }
 
+    /// Get the memory usage, flops and backends of a session in a single call
+    ///
+    /// This is equivalent to calling [`Interpreter::memory`] and [`Interpreter::flops`] (and
+    /// querying the backends) separately but only crosses the ffi boundary once, which is useful
+    /// when polling the values e.g. for every frame.
+    pub fn session_info(&self, session: &crate::Session) -> Result<SessionInfo> {
+        self.ensure_owns(session)?;
+        let mut info = mnn_sys::MNNSessionInfo {
+            memory: 0.0,
+            flops: 0.0,
+            backendCount: 0,
+            backends: [mnn_sys::MNNForwardType::MNN_FORWARD_CPU;
+                mnn_sys::MNN_SESSION_INFO_MAX_BACKENDS as usize],
+        };
+        let ret =
+            unsafe { mnn_sys::Interpreter_getSessionInfoAll(self.inner, session.inner, &mut info) };
+        ensure!(
+            ret == 1,
+            ErrorKind::InterpreterError;
+            "Failed to get session info"
+        );
+        let backends = info
+            .backends
+            .iter()
+            .take(info.backendCount as usize)
+            .map(|&backend| crate::ForwardType::from_mnn_sys(backend))
+            .collect();
+        Ok(SessionInfo {
+            memory: info.memory,
+            flops: info.flops,
+            backends,
+        })
+    }
+
     /// Get the resize status
     pub fn resize_status(&self, session: &crate::Session) -> Result<ResizeStatus> {
+        self.ensure_owns(session)?;
         let mut resize_status = 0i32;
         let ptr = &mut resize_status as *mut i32;
         let ret = unsafe {
@@ -610,6 +1266,17 @@ impl Interpreter {
     }
 }
 
+/// Memory usage, flops and backends of a session returned by [`Interpreter::session_info`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionInfo {
+    /// Memory usage of the session in MB, same as [`Interpreter::memory`]
+    pub memory: f32,
+    /// Float operations needed by the session in M, same as [`Interpreter::flops`]
+    pub flops: f32,
+    /// The main backend of every pipeline of the session
+    pub backends: Vec<crate::ForwardType>,
+}
+
 /// The status of the resize operation
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(C)]
@@ -675,6 +1342,19 @@ impl OperatorInfo<'_> {
     }
 }
 
+/// The time and flops of a single operator measured by [`Interpreter::profile_ops`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpProfile {
+    /// The name of the operator
+    pub name: String,
+    /// The type of the operator e.g. `Convolution`
+    pub op_type: String,
+    /// The float operations of the operator in M
+    pub flops: f32,
+    /// The time spent running the operator
+    pub time: std::time::Duration,
+}
+
 #[test]
 #[ignore = "This test doesn't work in CI"]
 fn test_run_session_with_callback_info_api() {
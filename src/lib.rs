@@ -37,6 +37,9 @@
 //! - `profile`: Enable profiling ( emits some profiling tracing events )
 //! - `tracing`: Enable tracing ( emits some tracing events )
 //! - `crt_static`: Link statically to the C runtime on windows (noop on other platforms)
+//! - `prebuilt`: Allow downloading a prebuilt mnn from `MNN_SYS_PREBUILT_URL` instead of compiling it
+//! - `image`: Enable loading images into tensors with `Tensor::from_image`
+//! - `tokio`: Enable `SharedSession::run_session_async` running sessions on tokio's blocking pool
 //! ## License
 //! This links to the MNN library which is licensed under the Apache License 2.0.  
 //! The rust bindings are licensed under the same Apache License 2.0.  
@@ -72,6 +75,8 @@ pub mod backend;
 pub mod error;
 /// MNN::Interpreter related items
 pub mod interpreter;
+/// Intercepting the log output of MNN
+pub mod log;
 /// Schedule configuration
 pub mod schedule;
 /// MNN::Session related items
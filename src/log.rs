@@ -0,0 +1,58 @@
+//! MNN reports its diagnostics (unsupported ops, backend fallbacks, ...) with `MNN_PRINT` and
+//! `MNN_ERROR`, which are routed to rust instead of `printf`. They are emitted as [`tracing`]
+//! events and can be intercepted with [`set_log_callback`].
+//!
+//! The messages are only routed when mnn-sys compiles MNN itself since the macros are patched
+//! in its headers. A system MNN (`MNN_SYS_USE_SYSTEM` / `MNN_LIB_DIR`) or a prebuilt library
+//! (`MNN_SYS_PREBUILT_URL`) still logs with `printf` so its messages never reach rust, the
+//! build script warns when that's the case.
+//!
+//! [`tracing`]: https://docs.rs/tracing
+pub use mnn_sys::LogLevel;
+
+/// Call `callback` with every message MNN logs (without the trailing newline), replacing the
+/// previously set callback
+///
+/// The callback is process wide and called from whichever thread MNN logs on, so it has to be
+/// `Send`. It must not call into MNN itself. Messages of an MNN that wasn't compiled by mnn-sys
+/// don't reach the callback, see the [module docs](self).
+///
+/// # Example
+/// ```rust,no_run
+/// # use mnn::*;
+/// let logs = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+/// let sink = logs.clone();
+/// mnn::log::set_log_callback(move |message| sink.lock().unwrap().push(message.to_owned()));
+/// let mut net = Interpreter::from_file("model.mnn").unwrap();
+/// if net.create_session(ScheduleConfig::new()).is_err() {
+///     eprintln!("MNN said: {:?}", logs.lock().unwrap());
+/// }
+/// mnn::log::clear_log_callback();
+/// ```
+pub fn set_log_callback(mut callback: impl FnMut(&str) + Send + 'static) {
+    mnn_sys::set_log_callback(Some(Box::new(move |_, message| callback(message))));
+}
+
+/// Like [`set_log_callback`] but the callback also receives the level of the message
+pub fn set_log_callback_with_level(callback: impl FnMut(LogLevel, &str) + Send + 'static) {
+    mnn_sys::set_log_callback(Some(Box::new(callback)));
+}
+
+/// Remove the callback set with [`set_log_callback`]
+pub fn clear_log_callback() {
+    mnn_sys::set_log_callback(None);
+}
+
+#[test]
+fn test_log_callback() {
+    use std::sync::{Arc, Mutex};
+    let logs = Arc::new(Mutex::new(Vec::<(LogLevel, String)>::new()));
+    let sink = logs.clone();
+    set_log_callback_with_level(move |level, message| {
+        sink.lock().unwrap().push((level, message.to_owned()))
+    });
+    // MNN reports why the model couldn't be loaded
+    assert!(crate::Interpreter::from_bytes([0u8; 64]).is_err());
+    clear_log_callback();
+    assert!(!logs.lock().unwrap().is_empty());
+}
@@ -59,7 +59,7 @@ pub enum ForwardType {
 
 impl ForwardType {
     /// Convert the `ForwardType` enum to the corresponding C++ `MNNForwardType` enum.
-    fn to_mnn_sys(self) -> MNNForwardType {
+    pub(crate) fn to_mnn_sys(self) -> MNNForwardType {
         match self {
             ForwardType::Auto => MNNForwardType::MNN_FORWARD_AUTO,
             ForwardType::All => MNNForwardType::MNN_FORWARD_ALL,
@@ -77,7 +77,7 @@ impl ForwardType {
         }
     }
 
-    fn from_mnn_sys(mode: MNNForwardType) -> Self {
+    pub(crate) fn from_mnn_sys(mode: MNNForwardType) -> Self {
         match mode {
             MNNForwardType::MNN_FORWARD_AUTO => ForwardType::Auto,
             MNNForwardType::MNN_FORWARD_ALL => ForwardType::All,
@@ -133,6 +133,55 @@ impl ForwardType {
             ForwardType::CoreML => "coreml",
         }
     }
+
+    /// Returns true for the GPU (and other accelerator) backends i.e. Metal, OpenCL, OpenGL,
+    /// Vulkan and CoreML
+    ///
+    /// [`ForwardType::Auto`] and [`ForwardType::All`] are neither GPU nor CPU since MNN picks the
+    /// backend
+    pub fn is_gpu(&self) -> bool {
+        match self {
+            ForwardType::Auto | ForwardType::All | ForwardType::CPU => false,
+            #[cfg(feature = "metal")]
+            ForwardType::Metal => true,
+            #[cfg(feature = "opencl")]
+            ForwardType::OpenCL => true,
+            #[cfg(feature = "opengl")]
+            ForwardType::OpenGL => true,
+            #[cfg(feature = "vulkan")]
+            ForwardType::Vulkan => true,
+            #[cfg(feature = "coreml")]
+            ForwardType::CoreML => true,
+        }
+    }
+
+    /// Returns true for the CPU backend
+    pub fn is_cpu(&self) -> bool {
+        *self == ForwardType::CPU
+    }
+
+    /// Returns true if the backend was compiled into MNN and can be created on this device
+    ///
+    /// GPU backends are checked by creating a runtime so this can be slow, cache the result if
+    /// you need it more than once. [`ForwardType::Auto`] and [`ForwardType::All`] are always
+    /// available since MNN falls back to the CPU.
+    pub fn is_available(self) -> bool {
+        match self {
+            ForwardType::Auto | ForwardType::All => true,
+            _ => unsafe { mnn_sys::isBackendAvailable(self.to_mnn_sys()) != 0 },
+        }
+    }
+
+    /// All the concrete backends (i.e. not [`ForwardType::Auto`] or [`ForwardType::All`]) that
+    /// are available on this device, see [`ForwardType::is_available`]
+    pub fn available() -> Vec<ForwardType> {
+        Self::list()
+            .into_iter()
+            .filter_map(|name| name.parse::<ForwardType>().ok())
+            .filter(|forward| !matches!(forward, ForwardType::Auto | ForwardType::All))
+            .filter(|forward| forward.is_available())
+            .collect()
+    }
 }
 
 impl core::str::FromStr for ForwardType {
@@ -211,6 +260,7 @@ impl core::str::FromStr for ForwardType {
 pub struct ScheduleConfig {
     pub(crate) inner: *mut MNNScheduleConfig,
     pub(crate) backend_config: Option<BackendConfig>,
+    pub(crate) memory_limit_mb: Option<u32>,
     pub(crate) __marker: core::marker::PhantomData<()>,
 }
 
@@ -219,7 +269,9 @@ impl core::fmt::Debug for ScheduleConfig {
         f.debug_struct("ScheduleConfig")
             .field("type", &self.get_type())
             .field("backup_type", &self.get_backup_type())
+            .field("num_threads", &self.get_num_threads())
             .field("backend_config", &self.backend_config)
+            .field("memory_limit_mb", &self.memory_limit_mb)
             .finish()
     }
 }
@@ -228,24 +280,71 @@ impl core::fmt::Debug for ScheduleConfig {
 impl serde::Serialize for ScheduleConfig {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("ScheduleConfig", 3)?;
+        let mut state = serializer.serialize_struct("ScheduleConfig", 5)?;
         state.serialize_field("type", &self.get_type())?;
         state.serialize_field("backup_type", &self.get_backup_type())?;
+        state.serialize_field("num_threads", &self.get_num_threads())?;
         state.serialize_field("backend_config", &self.backend_config)?;
+        match self.memory_limit_mb {
+            Some(memory_limit_mb) => state.serialize_field("memory_limit_mb", &memory_limit_mb)?,
+            None => state.skip_field("memory_limit_mb")?,
+        }
         state.end()
     }
 }
 
+/// Rebuilds the config with the setters from the fields written by the [`serde::Serialize`]
+/// implementation, the fields other than `type` are optional and keep their defaults if missing
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ScheduleConfig {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct Fields {
+            #[serde(rename = "type")]
+            forward: ForwardType,
+            #[serde(default)]
+            backup_type: Option<ForwardType>,
+            #[serde(default)]
+            num_threads: Option<i32>,
+            #[serde(default)]
+            backend_config: Option<BackendConfig>,
+            #[serde(default)]
+            memory_limit_mb: Option<u32>,
+        }
+        let fields = <Fields as serde::Deserialize>::deserialize(deserializer)?;
+        let mut config = ScheduleConfig::new();
+        config
+            .set_type(fields.forward)
+            .set_backend_config(fields.backend_config);
+        if let Some(backup_type) = fields.backup_type {
+            config.set_backup_type(backup_type);
+        }
+        if let Some(num_threads) = fields.num_threads {
+            config.set_num_threads(num_threads);
+        }
+        if let Some(memory_limit_mb) = fields.memory_limit_mb {
+            config.set_memory_limit_mb(memory_limit_mb);
+        }
+        Ok(config)
+    }
+}
+
+/// Deep copies the underlying `MNN::ScheduleConfig` and the attached [`BackendConfig`]
+///
+/// The copied C++ config would still point to the backend config of the original, so it's
+/// pointed to the cloned backend config instead. The clone is fully independent of the original
+/// and can outlive it.
 impl Clone for ScheduleConfig {
     fn clone(&self) -> Self {
-        unsafe {
-            let inner = mnnsc_clone(self.inner);
-            Self {
-                inner,
-                backend_config: self.backend_config.clone(),
-                __marker: core::marker::PhantomData,
-            }
-        }
+        let inner = unsafe { mnnsc_clone(self.inner) };
+        let mut config = Self {
+            inner,
+            backend_config: None,
+            memory_limit_mb: self.memory_limit_mb,
+            __marker: core::marker::PhantomData,
+        };
+        config.set_backend_config(self.backend_config.clone());
+        config
     }
 }
 
@@ -278,6 +377,7 @@ impl ScheduleConfig {
             Self {
                 inner,
                 backend_config: None,
+                memory_limit_mb: None,
                 __marker: core::marker::PhantomData,
             }
         }
@@ -387,6 +487,61 @@ impl ScheduleConfig {
         unsafe { ForwardType::from_mnn_sys(mnnsc_get_backup_type(self.inner)) }
     }
 
+    /// Gets the number of threads for the CPU backend.
+    ///
+    /// For GPU backends this is the same value as the mode set with [`ScheduleConfig::set_mode`].
+    pub fn get_num_threads(&self) -> i32 {
+        unsafe { mnnsc_get_num_threads(self.inner) }
+    }
+
+    /// Checks the config for invalid combinations of options before creating a session with it.
+    ///
+    /// The following are rejected
+    /// - Less than 1 thread for the CPU backend
+    /// - A backend that isn't available on this device
+    /// - [`PrecisionMode::Low`](crate::PrecisionMode::Low) on a backend that reports no fp16
+    ///   support
+    /// - [`PrecisionMode::LowBf16`](crate::PrecisionMode::LowBf16) on a backend other than the CPU
+    ///
+    /// Checking the backend creates a runtime for it (see [`crate::backend_features`]) so this
+    /// can be slow for GPU backends. [`ForwardType::Auto`] and [`ForwardType::All`] are not
+    /// checked against the device since MNN picks the backend.
+    pub fn validate(&self) -> Result<()> {
+        let forward = self.get_type();
+        if forward.is_cpu() {
+            let num_threads = self.get_num_threads();
+            ensure!(
+                num_threads >= 1,
+                ErrorKind::InvalidConfig;
+                format!("The CPU backend needs at least 1 thread, got {num_threads}")
+            );
+        }
+        if matches!(forward, ForwardType::Auto | ForwardType::All) {
+            return Ok(());
+        }
+        let features = crate::backend_features(forward);
+        ensure!(
+            features.available,
+            ErrorKind::InvalidConfig;
+            format!("The {} backend is not available on this device", forward.to_str())
+        );
+        let precision = self
+            .backend_config
+            .as_ref()
+            .map(|config| config.get_precision_mode());
+        ensure!(
+            precision != Some(crate::PrecisionMode::Low) || features.fp16 != Some(false),
+            ErrorKind::InvalidConfig;
+            format!("The {} backend doesn't support low (fp16) precision", forward.to_str())
+        );
+        ensure!(
+            precision != Some(crate::PrecisionMode::LowBf16) || forward.is_cpu(),
+            ErrorKind::InvalidConfig;
+            format!("Low bf16 precision is only supported on the CPU backend, not {}", forward.to_str())
+        );
+        Ok(())
+    }
+
     /// Sets the backend-specific configuration.
     ///
     /// # Arguments
@@ -413,6 +568,56 @@ impl ScheduleConfig {
         self.set_backend_config(backend_config);
         self
     }
+
+    /// Limit the memory a session created with this config may use in MB
+    ///
+    /// MNN has no allocation budget of its own so this is enforced by
+    /// [`crate::Interpreter::create_session`]: after MNN allocated the session its memory usage
+    /// (as reported by [`crate::Interpreter::memory`]) is checked against the limit and if it's
+    /// exceeded the session is released again and [`ErrorKind::MemoryLimitExceeded`] is
+    /// returned. The reported usage covers the buffers MNN tracks for every backend, memory
+    /// allocated by GPU drivers outside of MNN is not included. Multi path sessions ignore the
+    /// limit.
+    pub fn set_memory_limit_mb(&mut self, mb: u32) -> &mut Self {
+        self.memory_limit_mb = Some(mb);
+        self
+    }
+
+    /// Limit the memory a session created with this config may use in MB, see
+    /// [`ScheduleConfig::set_memory_limit_mb`]
+    pub fn with_memory_limit_mb(mut self, mb: u32) -> Self {
+        self.set_memory_limit_mb(mb);
+        self
+    }
+
+    /// Get the memory limit in MB, if any
+    pub fn get_memory_limit_mb(&self) -> Option<u32> {
+        self.memory_limit_mb
+    }
+
+    /// Sets the forward type, the power, precision and memory modes of the backend config and
+    /// optionally the number of threads in one call.
+    ///
+    /// This replaces any existing backend config, the number of threads is left unchanged if
+    /// `num_threads` is `None`.
+    pub fn configure(
+        &mut self,
+        forward: ForwardType,
+        power: crate::PowerMode,
+        precision: crate::PrecisionMode,
+        memory: crate::MemoryMode,
+        num_threads: impl Into<Option<i32>>,
+    ) -> &mut Self {
+        let backend_config = BackendConfig::new()
+            .with_power_mode(power)
+            .with_precision_mode(precision)
+            .with_memory_mode(memory);
+        self.set_type(forward).set_backend_config(backend_config);
+        if let Some(num_threads) = num_threads.into() {
+            self.set_num_threads(num_threads);
+        }
+        self
+    }
 }
 
 /// A list of `ScheduleConfig` objects to be used for scheduling the forward computation in MNN.
@@ -433,6 +638,13 @@ impl Drop for ScheduleConfigs {
 }
 
 impl ScheduleConfigs {
+    /// The forward type of every configuration
+    pub(crate) fn forward_types(&self) -> impl Iterator<Item = ForwardType> + '_ {
+        self.inner
+            .iter()
+            .map(|&config| unsafe { ForwardType::from_mnn_sys(mnnsc_get_type(config.cast_mut())) })
+    }
+
     /// Pushed a new `ScheduleConfig` to the list of configurations.
     pub fn push(&mut self, config: ScheduleConfig) {
         let mut config = ManuallyDrop::new(config);
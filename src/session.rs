@@ -1,8 +1,14 @@
-use crate::prelude::*;
+use crate::{HostTensorType, MutableTensorType, Tensor, prelude::*};
 
 /// A session is a context in which a computation graph is executed.
 ///
 /// Inference unit. multiple sessions could share one net/interpreter.
+///
+/// A session can only be used with the [`Interpreter`](crate::Interpreter) that created it. The
+/// methods of the interpreter that return a [`Result`](crate::error::Result) fail with
+/// [`ErrorKind::InterpreterError`](crate::ErrorKind::InterpreterError) when they are given a
+/// session of another interpreter, the infallible ones (e.g.
+/// [`Interpreter::inputs`](crate::Interpreter::inputs)) don't check it.
 #[derive(Debug)]
 pub struct Session {
     /// Pointer to the underlying MNN session.
@@ -20,6 +26,70 @@ pub struct Session {
     pub(crate) __marker: PhantomData<()>,
 }
 
+/// User owned host tensors copied into the inputs of a session before a run and out of its
+/// outputs after it, see [`crate::Interpreter::run_session_with`]
+///
+/// The tensors are borrowed for `'t` so they can't be dropped or modified while they are bound.
+#[derive(Debug, Default)]
+pub struct Bindings<'t> {
+    pub(crate) bindings: Vec<Binding>,
+    __marker: PhantomData<&'t mut ()>,
+}
+
+/// A user owned host tensor copied to / from a session tensor
+#[derive(Debug)]
+pub(crate) struct Binding {
+    pub(crate) name: String,
+    pub(crate) tensor: *mut mnn_sys::Tensor,
+    pub(crate) output: bool,
+}
+
+impl<'t> Bindings<'t> {
+    /// Create an empty set of bindings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copy `tensor` into the input `name` of the session before the run
+    ///
+    /// Binding another tensor to the same input replaces it
+    pub fn input<T: HostTensorType>(
+        &mut self,
+        name: impl Into<String>,
+        tensor: &'t Tensor<T>,
+    ) -> &mut Self
+    where
+        T::H: HalideType,
+    {
+        self.bind(name.into(), tensor.tensor, false)
+    }
+
+    /// Copy the output `name` of the session into `tensor` after the run
+    ///
+    /// Binding another tensor to the same output replaces it
+    pub fn output<T: HostTensorType + MutableTensorType>(
+        &mut self,
+        name: impl Into<String>,
+        tensor: &'t mut Tensor<T>,
+    ) -> &mut Self
+    where
+        T::H: HalideType,
+    {
+        self.bind(name.into(), tensor.tensor, true)
+    }
+
+    fn bind(&mut self, name: String, tensor: *mut mnn_sys::Tensor, output: bool) -> &mut Self {
+        self.bindings
+            .retain(|bound| bound.output != output || bound.name != name);
+        self.bindings.push(Binding {
+            name,
+            tensor,
+            output,
+        });
+        self
+    }
+}
+
 /// Enum representing the internal configurations of a session.
 #[derive(Debug)]
 pub enum SessionInternals {
@@ -32,6 +102,10 @@ pub enum SessionInternals {
 impl Session {
     /// Calls the destroy function on the underlying MNN session.
     pub fn destroy(&mut self) {
+        if self.inner.is_null() {
+            // Already released with `Interpreter::release_session`
+            return;
+        }
         unsafe {
             mnn_sys::Interpreter_releaseSession(self.net, self.inner);
         }
@@ -45,3 +119,89 @@ impl Drop for Session {
         self.destroy();
     }
 }
+
+/// An interpreter and one of its sessions shared between tasks, see
+/// [`SharedSession::run_session_async`]
+///
+/// Cloning is cheap and gives another handle to the same interpreter and session. The session is
+/// never shared without its interpreter, both are used behind one lock so only one thread at a
+/// time runs or accesses them, which is what makes the handle `Send` and `Sync` even though
+/// [`Session`] is neither.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone)]
+pub struct SharedSession {
+    inner: std::sync::Arc<std::sync::Mutex<SharedSessionInner>>,
+}
+
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+struct SharedSessionInner {
+    // Declared before the interpreter so the session is released first
+    session: Session,
+    interpreter: crate::Interpreter,
+}
+
+// SAFETY: the session only holds pointers into its interpreter, which is `Send`, and the two are
+// always moved together so the session is only ever used on the thread holding the interpreter
+#[cfg(feature = "tokio")]
+unsafe impl Send for SharedSessionInner {}
+
+#[cfg(feature = "tokio")]
+impl SharedSession {
+    /// Share `session` together with the `interpreter` that created it
+    ///
+    /// Fails with [`ErrorKind::InterpreterError`](crate::ErrorKind::InterpreterError) if the
+    /// session was created by another interpreter.
+    pub fn new(interpreter: crate::Interpreter, session: Session) -> Result<Self> {
+        interpreter.ensure_owns(&session)?;
+        Ok(Self {
+            inner: std::sync::Arc::new(std::sync::Mutex::new(SharedSessionInner {
+                session,
+                interpreter,
+            })),
+        })
+    }
+
+    /// Lock the interpreter and session and call `f` with them, e.g. to fill the inputs or read
+    /// the outputs
+    ///
+    /// This blocks until a run in progress is finished, avoid calling it from an async task while
+    /// [`SharedSession::run_session_async`] may be running.
+    pub fn with<R>(&self, f: impl FnOnce(&mut crate::Interpreter, &mut Session) -> R) -> R {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let SharedSessionInner {
+            session,
+            interpreter,
+        } = &mut *inner;
+        f(interpreter, session)
+    }
+
+    /// Run the session on tokio's blocking thread pool and wait for it to finish without blocking
+    ///
+    /// This is [`Interpreter::run_session`](crate::Interpreter::run_session) on the blocking
+    /// thread. The future holds its own handle to the interpreter and session so it is `'static`
+    /// and can be spawned. Dropping it doesn't cancel a run that already started, the run finishes
+    /// in the background and the interpreter and session are freed afterwards if this was the last
+    /// handle.
+    ///
+    /// Runs of the same shared session are serialized, use a session per task to run them in
+    /// parallel.
+    pub fn run_session_async(
+        &self,
+    ) -> impl core::future::Future<Output = Result<()>> + Send + 'static {
+        let inner = self.inner.clone();
+        async move {
+            tokio::task::spawn_blocking(move || {
+                let mut inner = inner.lock().unwrap_or_else(|e| e.into_inner());
+                let SharedSessionInner {
+                    session,
+                    interpreter,
+                } = &mut *inner;
+                interpreter.run_session(session)
+            })
+            .await
+            .change_context(ErrorKind::InterpreterError)
+            .attach_printable("The session run panicked or was cancelled")?
+        }
+    }
+}
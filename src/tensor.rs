@@ -1,8 +1,13 @@
 use crate::prelude::*;
 use core::marker::PhantomData;
 use mnn_sys::*;
+#[cfg(feature = "image")]
+mod image;
+mod io;
 pub(crate) mod list;
+mod ops;
 mod raw;
+pub use ops::CastFrom;
 pub use raw::RawTensor;
 
 use mnn_sys::HalideType;
@@ -120,12 +125,15 @@ pub struct RefMut<'t, T> {
 /// A generic tensor that can of host / device / owned / borrowed
 pub struct Tensor<T: TensorType> {
     pub(crate) tensor: *mut mnn_sys::Tensor,
+    /// The MNN tensor object was created for this borrowed tensor (e.g. by
+    /// [`Tensor::borrowed`]) so it's destroyed on drop, the borrowed data is never freed
+    owns_object: bool,
     __marker: PhantomData<T>,
 }
 
 impl<T: TensorType> Drop for Tensor<T> {
     fn drop(&mut self) {
-        if T::owned() {
+        if T::owned() || self.owns_object {
             unsafe {
                 mnn_sys::Tensor_destroy(self.tensor);
             }
@@ -138,9 +146,196 @@ impl<H: HalideType> Tensor<Host<H>> {
     pub fn as_ref(&self) -> Tensor<Ref<'_, Host<H>>> {
         Tensor {
             tensor: self.tensor,
+            owns_object: false,
             __marker: PhantomData,
         }
     }
+
+    /// Create a tensor owning a copy of `data`
+    ///
+    /// Unlike [`Tensor::borrowed`] the tensor allocates its own buffer so it can outlive `data`.
+    /// `data` is copied as is, so it must already be laid out according to `dm_type`.
+    ///
+    /// Panics if the length of `data` is not the number of elements of the shape
+    pub fn new_with_data(shape: impl AsTensorShape, dm_type: DimensionType, data: &[H]) -> Self {
+        let shape = shape.as_tensor_shape();
+        let elements = shape.num_elements().unwrap_or(0);
+        assert_eq!(
+            data.len(),
+            elements,
+            "The data has {} elements but the shape {shape:?} has {elements}",
+            data.len()
+        );
+        let mut tensor = Self::new(shape, dm_type);
+        debug_assert!(tensor.host().len() >= data.len());
+        // SAFETY: the tensor was allocated with room for all the elements of the shape
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                data.as_ptr(),
+                tensor.host_mut().as_mut_ptr(),
+                data.len(),
+            )
+        };
+        tensor
+    }
+
+    /// Create a [`DimensionType::Caffe`] tensor with the elements of an iterator
+    ///
+    /// The iterator must yield exactly as many elements as the shape has, otherwise
+    /// [`ErrorKind::SizeMismatch`] with the number of elements it yielded is returned. The
+    /// remaining elements are counted for the error so the iterator must be finite.
+    pub fn from_iter_with_shape(
+        shape: impl AsTensorShape,
+        iter: impl IntoIterator<Item = H>,
+    ) -> Result<Self> {
+        let mut tensor = Self::new(shape, DimensionType::Caffe);
+        let expected = tensor.len();
+        let mut iter = iter.into_iter();
+        let got = tensor
+            .try_host_mut()?
+            .iter_mut()
+            .zip(iter.by_ref())
+            .map(|(dst, src)| *dst = src)
+            .count();
+        ensure!(
+            got == expected,
+            ErrorKind::SizeMismatch { expected, got };
+            "Iterator yielded fewer elements than the shape"
+        );
+        let extra = iter.count();
+        ensure!(
+            extra == 0,
+            ErrorKind::SizeMismatch {
+                expected,
+                got: expected + extra,
+            };
+            "Iterator yielded more elements than the shape"
+        );
+        Ok(tensor)
+    }
+
+    /// Iterate over views of `n` slices of the outer (batch) dimension at a time
+    ///
+    /// The views borrow the data of the tensor so no data is copied, the last chunk is smaller
+    /// if the outer dimension is not divisible by `n`. The outer dimension is only contiguous for
+    /// the [`DimensionType::Caffe`] and [`DimensionType::TensorFlow`] layouts.
+    ///
+    /// Panics if `n` is 0
+    pub fn outer_chunks(&self, n: usize) -> impl Iterator<Item = Tensor<Ref<'_, Host<H>>>> + '_ {
+        assert!(n != 0, "Chunk size must be non-zero");
+        let shape = self.shape();
+        let outer = shape.first().map_or(1, |&dim| dim.max(0) as usize);
+        let data = self.host();
+        let stride = data.len().checked_div(outer).unwrap_or(0);
+        let dm_type = self.get_dimension_type().to_mnn_sys();
+        (0..outer).step_by(n).map(move |start| {
+            let mut chunk_shape = shape;
+            chunk_shape.shape[0] = n.min(outer - start) as i32;
+            let tensor = unsafe {
+                Tensor_createWith(
+                    chunk_shape.shape.as_ptr(),
+                    chunk_shape.size,
+                    halide_type_of::<H>(),
+                    data[start * stride..].as_ptr().cast_mut().cast(),
+                    dm_type,
+                )
+            };
+            debug_assert!(!tensor.is_null());
+            Tensor {
+                tensor,
+                owns_object: true,
+                __marker: PhantomData,
+            }
+        })
+    }
+
+    /// Copy the tensor with a leading batch dimension of size 1 e.g. `[3, 224, 224]` to
+    /// `[1, 3, 224, 224]`
+    ///
+    /// Fails if the tensor already has 4 dimensions or uses [`DimensionType::CaffeC4`] where
+    /// adding an axis changes the layout of the data
+    pub fn with_batch_dim(&self) -> Result<Self> {
+        let shape = self.shape();
+        ensure!(
+            shape.len() < 4,
+            ErrorKind::TensorError;
+            format!("Cannot add a batch dimension to a tensor of shape {shape:?}")
+        );
+        let dims = core::iter::once(1)
+            .chain(shape.iter().copied())
+            .collect::<Vec<_>>();
+        self.copy_with_shape(dims)
+    }
+
+    /// Copy the tensor without its leading batch dimension e.g. `[1, 3, 224, 224]` to
+    /// `[3, 224, 224]`
+    ///
+    /// Fails if the leading dimension is not 1 or the tensor uses [`DimensionType::CaffeC4`]
+    pub fn without_batch_dim(&self) -> Result<Self> {
+        let shape = self.shape();
+        ensure!(
+            shape.first() == Some(&1),
+            ErrorKind::TensorError;
+            format!("Cannot remove the batch dimension of a tensor of shape {shape:?}")
+        );
+        self.copy_with_shape(shape.iter().skip(1).copied().collect::<Vec<_>>())
+    }
+
+    /// Copy the data into a new tensor with the same number of elements but a different shape
+    fn copy_with_shape(&self, shape: impl AsTensorShape) -> Result<Self> {
+        let dm_type = self.get_dimension_type();
+        ensure!(
+            dm_type != DimensionType::CaffeC4,
+            ErrorKind::TensorError;
+            "Cannot reshape a tensor with the CaffeC4 dimension type"
+        );
+        let mut tensor = Self::new(shape, dm_type);
+        let data = self.try_host()?;
+        let host = tensor.try_host_mut()?;
+        ensure!(
+            host.len() == data.len(),
+            ErrorKind::SizeMismatch {
+                expected: host.len(),
+                got: data.len(),
+            }
+        );
+        // Halide types are plain old data so a bitwise copy is fine
+        unsafe { core::ptr::copy_nonoverlapping(data.as_ptr(), host.as_mut_ptr(), data.len()) };
+        Ok(tensor)
+    }
+
+    /// Change the shape of the tensor in place without moving or reallocating its data
+    ///
+    /// The new shape must have the same number of elements as the current one, e.g. to flatten
+    /// `[N, C, H, W]` to `[N, C * H * W]`. Only owned host tensors can be reshaped, the layout of
+    /// a device tensor's allocation is up to its backend and session tensors are resized with
+    /// [`Interpreter::resize_tensor`](crate::Interpreter::resize_tensor).
+    ///
+    /// Fails with [`ErrorKind::SizeMismatch`] if the number of elements differs or
+    /// [`ErrorKind::TensorError`] for [`DimensionType::CaffeC4`] tensors whose packed layout
+    /// depends on the shape
+    pub fn reshape(&mut self, shape: impl AsTensorShape) -> Result<()> {
+        let shape = shape.as_tensor_shape();
+        let current = self.shape();
+        let expected = current
+            .num_elements()
+            .ok_or_else(|| error!(ErrorKind::DynamicTensorError))?;
+        let got = shape
+            .num_elements()
+            .ok_or_else(|| error!(ErrorKind::DynamicTensorError))?;
+        ensure!(
+            expected == got,
+            ErrorKind::SizeMismatch { expected, got };
+            format!("Cannot reshape a tensor of shape {current:?} into {shape:?}")
+        );
+        let ret = unsafe { mnn_sys::Tensor_reshape(self.tensor, shape.shape.as_ptr(), shape.size) };
+        ensure!(
+            ret != 0,
+            ErrorKind::TensorError;
+            "Cannot reshape a tensor with the CaffeC4 dimension type"
+        );
+        Ok(())
+    }
 }
 
 impl<H: HalideType> Tensor<Device<H>> {
@@ -148,6 +343,7 @@ impl<H: HalideType> Tensor<Device<H>> {
     pub fn as_ref(&self) -> Tensor<Ref<'_, Device<H>>> {
         Tensor {
             tensor: self.tensor,
+            owns_object: false,
             __marker: PhantomData,
         }
     }
@@ -160,6 +356,7 @@ impl<H: HalideType> Tensor<Device<H>> {
 /// H -> Height
 /// W -> Width
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DimensionType {
     /// Caffe style dimensions or NCHW
     Caffe,
@@ -183,6 +380,67 @@ impl DimensionType {
             DimensionType::TensorFlow => mnn_sys::DimensionType::TENSORFLOW,
         }
     }
+
+    /// The strides (in elements) of the batch, channel, height and width dimensions of a 4
+    /// dimensional host buffer with this layout
+    ///
+    /// `shape` is in the order of the layout i.e. `[n, c, h, w]` for [`DimensionType::Caffe`]
+    /// and `[n, h, w, c]` for [`DimensionType::TensorFlow`] (see [`TensorShape::nchw`] and
+    /// [`TensorShape::nhwc`]). The strides are always returned as `[n, c, h, w]` so the
+    /// element at `(n, c, h, w)` is at `n * s[0] + c * s[1] + h * s[2] + w * s[3]` in both
+    /// layouts. [`Tensor::logical_strides`] instead returns the strides in the order of the shape
+    /// for any number of dimensions, which is the same as these for [`DimensionType::Caffe`] only.
+    ///
+    /// Fails with [`ErrorKind::TensorError`] for [`DimensionType::CaffeC4`] since the channels
+    /// are padded and packed in groups of 4 which can't be expressed with strides, or if `shape`
+    /// doesn't have 4 dimensions.
+    pub fn strides(&self, shape: &TensorShape) -> Result<[i32; 4]> {
+        ensure!(
+            shape.size == 4,
+            ErrorKind::TensorError;
+            format!("Expected a 4 dimensional shape, got {shape:?}")
+        );
+        let [_, d1, d2, d3] = shape.shape;
+        match self {
+            DimensionType::Caffe => Ok([d1 * d2 * d3, d2 * d3, d3, 1]),
+            DimensionType::TensorFlow => Ok([d1 * d2 * d3, 1, d2 * d3, d3]),
+            DimensionType::CaffeC4 => Err(error!(ErrorKind::TensorError).attach_printable(
+                "NC4HW4 tensors have no strides, convert them with Tensor::to_dimension_type first",
+            )),
+        }
+    }
+
+    /// Convert the `DimensionType` to its layout name (nchw / nhwc / nc4hw4)
+    pub fn to_str(self) -> &'static str {
+        match self {
+            DimensionType::Caffe => "nchw",
+            DimensionType::CaffeC4 => "nc4hw4",
+            DimensionType::TensorFlow => "nhwc",
+        }
+    }
+}
+
+impl core::fmt::Display for DimensionType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.to_str())
+    }
+}
+
+impl core::str::FromStr for DimensionType {
+    type Err = MNNError;
+
+    /// Parses the layout names (nchw / nhwc / nc4hw4) as well as the MNN names
+    /// (caffe / tensorflow / caffe_c4) case-insensitively
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "nchw" | "caffe" => Ok(DimensionType::Caffe),
+            "nc4hw4" | "caffe_c4" | "caffec4" => Ok(DimensionType::CaffeC4),
+            "nhwc" | "tensorflow" | "tf" => Ok(DimensionType::TensorFlow),
+            _ => Err(error!(ErrorKind::ParseError)
+                .attach_printable(format!("invalid dimension type: {s}"))
+                .attach_printable("Valid dimension types: nchw, nhwc, nc4hw4")),
+        }
+    }
 }
 
 impl From<mnn_sys::DimensionType> for DimensionType {
@@ -195,6 +453,226 @@ impl From<mnn_sys::DimensionType> for DimensionType {
     }
 }
 
+/// How the host intends to access a device tensor's memory
+/// This is used when waiting on (or mapping) a device tensor so that MNN knows in which direction
+/// the data needs to be synchronized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TensorMapType {
+    /// The host will write to the tensor
+    /// Use this before filling an input tensor, MNN will upload the data to the device afterwards.
+    /// Reading from a tensor mapped with `Write` may give stale data.
+    Write,
+    /// The host will read from the tensor
+    /// Use this for output tensors after running a session, MNN will make sure the data computed on
+    /// the device is visible to the host.
+    Read,
+}
+
+impl TensorMapType {
+    pub(crate) fn to_mnn_sys(self) -> mnn_sys::MapType {
+        match self {
+            TensorMapType::Write => mnn_sys::MapType::MAP_TENSOR_WRITE,
+            TensorMapType::Read => mnn_sys::MapType::MAP_TENSOR_READ,
+        }
+    }
+}
+
+impl From<mnn_sys::MapType> for TensorMapType {
+    fn from(mt: mnn_sys::MapType) -> Self {
+        match mt {
+            mnn_sys::MapType::MAP_TENSOR_WRITE => TensorMapType::Write,
+            mnn_sys::MapType::MAP_TENSOR_READ => TensorMapType::Read,
+        }
+    }
+}
+
+impl From<TensorMapType> for mnn_sys::MapType {
+    fn from(mt: TensorMapType) -> Self {
+        mt.to_mnn_sys()
+    }
+}
+
+/// The data type of the elements of a tensor
+///
+/// This is the runtime equivalent of the [`HalideType`] of a typed [`Tensor`], useful for
+/// [`RawTensor`]s whose type isn't known at compile time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TensorDataType {
+    /// Boolean stored as a byte
+    Bool,
+    /// 8-bit unsigned integer
+    U8,
+    /// 16-bit unsigned integer
+    U16,
+    /// 32-bit unsigned integer
+    U32,
+    /// 64-bit unsigned integer
+    U64,
+    /// 8-bit signed integer
+    I8,
+    /// 16-bit signed integer
+    I16,
+    /// 32-bit signed integer
+    I32,
+    /// 64-bit signed integer
+    I64,
+    /// 16-bit float
+    F16,
+    /// 32-bit float
+    F32,
+    /// 64-bit float
+    F64,
+    /// 16-bit brain float
+    BF16,
+    /// Opaque pointer, see [`HandleDataType`]
+    Handle,
+    /// Any other halide type
+    Other {
+        /// The halide type code
+        code: u8,
+        /// Number of bits of a single lane
+        bits: u8,
+        /// Number of lanes
+        lanes: u16,
+    },
+}
+
+impl TensorDataType {
+    /// The data type of the rust type `H`
+    pub fn of<H: HalideType>() -> Self {
+        Self::from(halide_type_of::<H>())
+    }
+
+    /// The data type of a halide type given by its code, bits and lanes
+    pub(crate) fn from_parts(code: u8, bits: u8, lanes: u16) -> Self {
+        use mnn_sys::halide_type_code_t::*;
+        const INT: u8 = halide_type_int as u8;
+        const UINT: u8 = halide_type_uint as u8;
+        const FLOAT: u8 = halide_type_float as u8;
+        const HANDLE: u8 = halide_type_handle as u8;
+        const BFLOAT: u8 = halide_type_bfloat as u8;
+        match (code, bits, lanes) {
+            (UINT, 1, 1) => Self::Bool,
+            (UINT, 8, 1) => Self::U8,
+            (UINT, 16, 1) => Self::U16,
+            (UINT, 32, 1) => Self::U32,
+            (UINT, 64, 1) => Self::U64,
+            (INT, 8, 1) => Self::I8,
+            (INT, 16, 1) => Self::I16,
+            (INT, 32, 1) => Self::I32,
+            (INT, 64, 1) => Self::I64,
+            (FLOAT, 16, 1) => Self::F16,
+            (FLOAT, 32, 1) => Self::F32,
+            (FLOAT, 64, 1) => Self::F64,
+            (BFLOAT, 16, 1) => Self::BF16,
+            (HANDLE, _, 1) => Self::Handle,
+            (code, bits, lanes) => Self::Other { code, bits, lanes },
+        }
+    }
+
+    /// The name of the rust type of the elements e.g. `"f32"`, `"f16"` or `"bf16"` for the
+    /// halide types without a rust equivalent and `"unknown"` for [`TensorDataType::Other`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bool => "bool",
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::F16 => "f16",
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+            Self::BF16 => "bf16",
+            Self::Handle => "handle",
+            Self::Other { .. } => "unknown",
+        }
+    }
+
+    /// Size of a single element in bytes
+    pub fn byte_size(&self) -> usize {
+        match self {
+            Self::Bool | Self::U8 | Self::I8 => 1,
+            Self::U16 | Self::I16 | Self::F16 | Self::BF16 => 2,
+            Self::U32 | Self::I32 | Self::F32 => 4,
+            Self::U64 | Self::I64 | Self::F64 => 8,
+            Self::Handle => core::mem::size_of::<*mut c_void>(),
+            Self::Other { bits, lanes, .. } => (*bits as usize).div_ceil(8) * *lanes as usize,
+        }
+    }
+}
+
+impl From<mnn_sys::halide_type_t> for TensorDataType {
+    fn from(ht: mnn_sys::halide_type_t) -> Self {
+        Self::from_parts(ht.code as u8, ht.bits, ht.lanes)
+    }
+}
+
+/// The kind of handle stored in a tensor with the halide `handle` type
+/// This is only meaningful for tensors whose elements are pointers, for all numeric tensors it's
+/// [`HandleDataType::None`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum HandleDataType {
+    /// The tensor doesn't hold handles or the handles are opaque
+    #[default]
+    None,
+    /// Each element of the tensor is a pointer to a string
+    String,
+}
+
+impl From<mnn_sys::HandleDataType> for HandleDataType {
+    fn from(ht: mnn_sys::HandleDataType) -> Self {
+        match ht {
+            mnn_sys::HandleDataType::HANDLE_NONE => HandleDataType::None,
+            mnn_sys::HandleDataType::HANDLE_STRING => HandleDataType::String,
+        }
+    }
+}
+
+impl From<HandleDataType> for mnn_sys::HandleDataType {
+    fn from(ht: HandleDataType) -> Self {
+        match ht {
+            HandleDataType::None => mnn_sys::HandleDataType::HANDLE_NONE,
+            HandleDataType::String => mnn_sys::HandleDataType::HANDLE_STRING,
+        }
+    }
+}
+
+impl<H: HalideType + PartialEq> PartialEq for Tensor<Host<H>> {
+    /// Two host tensors are equal if they have the same shape (see [`TensorShape`]), dimension
+    /// type and elements
+    fn eq(&self, other: &Self) -> bool {
+        self.shape() == other.shape()
+            && self.get_dimension_type() == other.get_dimension_type()
+            && self.host() == other.host()
+    }
+}
+
+impl<H> Tensor<Host<H>>
+where
+    H: HalideType + PartialOrd + Copy + core::ops::Sub<Output = H>,
+{
+    /// Compare two tensors allowing the elements to differ by at most `epsilon`
+    ///
+    /// Use this instead of `==` for float outputs which drift slightly across backends and
+    /// precisions. The shapes and dimension types still have to match exactly and a `NaN`
+    /// element is never equal to anything.
+    pub fn approx_eq(&self, other: &Self, epsilon: H) -> bool {
+        self.shape() == other.shape()
+            && self.get_dimension_type() == other.get_dimension_type()
+            && self.host().iter().zip(other.host()).all(|(&a, &b)| {
+                if a > b {
+                    a - b <= epsilon
+                } else {
+                    b - a <= epsilon
+                }
+            })
+    }
+}
+
 impl<T: TensorType> Tensor<T>
 where
     T::H: HalideType,
@@ -207,6 +685,7 @@ where
         assert!(!tensor.is_null());
         Self {
             tensor,
+            owns_object: false,
             __marker: PhantomData,
         }
     }
@@ -224,6 +703,101 @@ where
         Ok(())
     }
 
+    /// Copy the data of the tensor into an owned [`Vec`]
+    ///
+    /// Device tensors are copied to a host tensor first. Fails if the tensor is not of type
+    /// `T::H`
+    pub fn try_into_vec(&self) -> Result<Vec<T::H>>
+    where
+        T::H: Clone,
+    {
+        ensure!(
+            self.is_type_of::<T::H>(),
+            ErrorKind::HalideTypeMismatch {
+                got: std::any::type_name::<T::H>(),
+            };
+            format!("The tensor holds {}", self.dtype_name())
+        );
+        if self.element_size() == 0 {
+            Ok(Vec::new())
+        } else if T::host() {
+            let data = unsafe {
+                core::slice::from_raw_parts(
+                    mnn_sys::Tensor_host(self.tensor).cast::<T::H>(),
+                    self.element_size(),
+                )
+            };
+            Ok(data.to_vec())
+        } else {
+            let mut host = Tensor::<Host<T::H>>::new(self.shape(), self.get_dimension_type());
+            self.copy_to_host_tensor(&mut host)?;
+            Ok(host.try_host()?.to_vec())
+        }
+    }
+
+    /// Copy the tensor into a new host tensor with the `target` layout
+    ///
+    /// The data is repacked rather than reinterpreted, e.g. the channels of a
+    /// [`DimensionType::NC4HW4`] tensor are unpacked and the padding is dropped when converting
+    /// to [`DimensionType::NCHW`]. Device tensors are converted by the backend while copying them
+    /// to the host.
+    ///
+    /// The shape of the result is in the order of the target layout i.e. `[n, h, w, c]` for
+    /// [`DimensionType::NHWC`] and `[n, c, h, w]` otherwise.
+    pub fn to_dimension_type(&self, target: DimensionType) -> Result<Tensor<Host<T::H>>>
+    where
+        T::H: Copy,
+    {
+        ensure!(
+            self.is_type_of::<T::H>(),
+            ErrorKind::HalideTypeMismatch {
+                got: std::any::type_name::<T::H>(),
+            };
+            format!("The tensor holds {}", self.dtype_name())
+        );
+        let source = self.get_dimension_type();
+        let mut shape = self.shape().to_vec();
+        if shape.len() > 2 {
+            match (source, target) {
+                (DimensionType::TensorFlow, DimensionType::Caffe | DimensionType::CaffeC4) => {
+                    shape[1..].rotate_right(1)
+                }
+                (DimensionType::Caffe | DimensionType::CaffeC4, DimensionType::TensorFlow) => {
+                    shape[1..].rotate_left(1)
+                }
+                _ => {}
+            }
+        }
+        let mut out = Tensor::<Host<T::H>>::new(&shape, target);
+        if T::device() {
+            self.copy_to_host_tensor(&mut out)?;
+            return Ok(out);
+        }
+
+        let from = ChannelLayout::new(&self.shape(), source);
+        let to = ChannelLayout::new(&out.shape(), target);
+        let (src, dst) = unsafe {
+            (
+                core::slice::from_raw_parts(
+                    Tensor_host(self.tensor).cast::<T::H>(),
+                    self.size() / core::mem::size_of::<T::H>(),
+                ),
+                core::slice::from_raw_parts_mut(
+                    Tensor_host_mut(out.tensor).cast::<T::H>(),
+                    out.size() / core::mem::size_of::<T::H>(),
+                ),
+            )
+        };
+        for n in 0..from.batch {
+            for c in 0..from.channel {
+                for s in 0..from.spatial {
+                    dst[to.offset(n, c, s)] = src[from.offset(n, c, s)];
+                }
+            }
+        }
+        Ok(out)
+    }
+
     /// Get the device id of the tensor
     pub fn device_id(&self) -> u64 {
         unsafe { Tensor_deviceId(self.tensor) }
@@ -308,11 +882,42 @@ where
         From::from(unsafe { Tensor_getDimensionType(self.tensor) })
     }
 
+    /// Returns true if the elements are laid out contiguously in the order of the shape
+    ///
+    /// This is false for [`DimensionType::CaffeC4`] (NC4HW4) tensors where the channels are
+    /// packed in groups of 4 i.e. the data is laid out as `[N, ceil(C / 4), H, W, 4]` with the
+    /// last group padded, so the host data can't be indexed with the shape directly
+    pub fn is_contiguous(&self) -> bool {
+        self.get_dimension_type() != DimensionType::CaffeC4
+    }
+
+    /// The strides (in elements) of each dimension of a contiguous tensor with the same shape
+    ///
+    /// For contiguous tensors these are the actual strides of the host data, for
+    /// [`DimensionType::CaffeC4`] tensors (see [`Tensor::is_contiguous`]) they are the strides
+    /// the data would have after converting it to [`DimensionType::Caffe`]. The strides are in
+    /// the order of the shape, use [`DimensionType::strides`] to get them in `[n, c, h, w]` order
+    /// regardless of the layout.
+    pub fn logical_strides(&self) -> Vec<usize> {
+        let shape = self.shape();
+        let mut strides = vec![1; shape.len()];
+        for i in (0..shape.len().saturating_sub(1)).rev() {
+            strides[i] = strides[i + 1] * shape[i + 1].max(0) as usize;
+        }
+        strides
+    }
+
     /// Get the data type of the tensor
     pub fn get_type(&self) -> mnn_sys::halide_type_t {
         unsafe { Tensor_getType(self.tensor) }
     }
 
+    /// The name of the data type the tensor actually holds e.g. `"f32"` or `"u8"`, see
+    /// [`TensorDataType::name`]
+    pub fn dtype_name(&self) -> &'static str {
+        TensorDataType::from(self.get_type()).name()
+    }
+
     /// Check if the tensor is of the specified data type
     pub fn is_type_of<H: HalideType>(&self) -> bool {
         let htc = halide_type_of::<H>();
@@ -335,13 +940,34 @@ where
     T::H: HalideType,
 {
     /// Fill the tensor with the specified value
+    ///
+    /// Panics if the tensor is not of type `T::H` or the copy to a device tensor fails, see
+    /// [`Tensor::try_fill`]
     pub fn fill(&mut self, value: T::H)
     where
         T::H: Copy,
     {
+        self.try_fill(value).expect("Failed to fill tensor");
+    }
+
+    /// Fill the tensor with the specified value
+    ///
+    /// Fails with [`ErrorKind::HalideTypeMismatch`] if the tensor is not of type `T::H` or with
+    /// [`ErrorKind::TensorCopyFailed`] if the data can't be copied to a device tensor e.g. when
+    /// the backend is out of memory
+    pub fn try_fill(&mut self, value: T::H) -> Result<()>
+    where
+        T::H: Copy,
+    {
+        ensure!(
+            self.is_type_of::<T::H>(),
+            ErrorKind::HalideTypeMismatch {
+                got: std::any::type_name::<T::H>(),
+            };
+            format!("The tensor holds {}", self.dtype_name())
+        );
         if T::host() {
             let size = self.element_size();
-            assert!(self.is_type_of::<T::H>());
             let result: &mut [T::H] = unsafe {
                 let data = mnn_sys::Tensor_host_mut(self.tensor).cast();
                 core::slice::from_raw_parts_mut(data, size)
@@ -351,7 +977,32 @@ where
             let shape = self.shape();
             let dm_type = self.get_dimension_type();
             let mut host = Tensor::new(shape, dm_type);
-            host.fill(value);
+            host.try_fill(value)?;
+            self.copy_from_host_tensor(&host)?;
+        } else {
+            unreachable!()
+        }
+        Ok(())
+    }
+
+    /// Fill the tensor with the values returned by `f` for the flat index of each element
+    pub fn fill_with<F: FnMut(usize) -> T::H>(&mut self, mut f: F) {
+        if T::host() {
+            let size = self.element_size();
+            assert!(self.is_type_of::<T::H>());
+            let result: &mut [T::H] = unsafe {
+                let data = mnn_sys::Tensor_host_mut(self.tensor).cast();
+                core::slice::from_raw_parts_mut(data, size)
+            };
+            result
+                .iter_mut()
+                .enumerate()
+                .for_each(|(index, value)| *value = f(index));
+        } else if T::device() {
+            let shape = self.shape();
+            let dm_type = self.get_dimension_type();
+            let mut host = Tensor::new(shape, dm_type);
+            host.fill_with(f);
             self.copy_from_host_tensor(&host)
                 .expect("Failed to copy data from host tensor");
         } else {
@@ -360,6 +1011,35 @@ where
     }
 }
 
+impl<T: DeviceTensorType + MutableTensorType> Tensor<T>
+where
+    T::H: HalideType,
+{
+    /// Set all the elements of the device tensor to zero
+    ///
+    /// If the memory of the tensor is host accessible (e.g. tensors of the CPU backend) it's
+    /// written directly, otherwise a zeroed host tensor is copied to the device like
+    /// [`Tensor::fill`] does. MNN has no backend side clear, so this is not faster than `fill`
+    /// for GPU tensors.
+    pub fn zero(&mut self) {
+        let host = unsafe { Tensor_host_mut(self.tensor) };
+        if !host.is_null() {
+            unsafe { core::ptr::write_bytes(host.cast::<u8>(), 0, Tensor_usize(self.tensor)) };
+            return;
+        }
+        let staging = Tensor::<Host<T::H>>::new(self.shape(), self.get_dimension_type());
+        unsafe {
+            core::ptr::write_bytes(
+                Tensor_host_mut(staging.tensor).cast::<u8>(),
+                0,
+                Tensor_usize(staging.tensor),
+            )
+        };
+        self.copy_from_host_tensor(&staging)
+            .expect("Failed to copy data from host tensor");
+    }
+}
+
 impl<T: HostTensorType> Tensor<T>
 where
     T::H: HalideType,
@@ -371,7 +1051,8 @@ where
             self.is_type_of::<T::H>(),
             ErrorKind::HalideTypeMismatch {
                 got: std::any::type_name::<T::H>(),
-            }
+            };
+            format!("The tensor holds {}", self.dtype_name())
         );
         let result = unsafe {
             let data = mnn_sys::Tensor_host(self.tensor).cast();
@@ -387,7 +1068,8 @@ where
             self.is_type_of::<T::H>(),
             ErrorKind::HalideTypeMismatch {
                 got: std::any::type_name::<T::H>(),
-            }
+            };
+            format!("The tensor holds {}", self.dtype_name())
         );
 
         let result = unsafe {
@@ -407,6 +1089,221 @@ where
     pub fn host_mut(&mut self) -> &mut [T::H] {
         self.try_host_mut().expect("Failed to get tensor host_mut")
     }
+
+    /// Copy `data` into the host data of the tensor
+    ///
+    /// Fails with [`ErrorKind::SizeMismatch`] if `data` doesn't have exactly as many elements as
+    /// the tensor or [`ErrorKind::HalideTypeMismatch`] if the tensor is not of type `T::H`
+    pub fn copy_from_slice(&mut self, data: &[T::H]) -> Result<()> {
+        let host = self.try_host_mut()?;
+        ensure!(
+            host.len() == data.len(),
+            ErrorKind::SizeMismatch {
+                expected: host.len(),
+                got: data.len(),
+            };
+            format!(
+                "Cannot copy {} elements into a tensor of {} elements",
+                data.len(),
+                host.len()
+            )
+        );
+        // Halide types are plain old data so a bitwise copy is fine
+        unsafe { core::ptr::copy_nonoverlapping(data.as_ptr(), host.as_mut_ptr(), data.len()) };
+        Ok(())
+    }
+
+    /// Iterate over the elements of the host data of the tensor
+    pub fn iter(&self) -> core::slice::Iter<'_, T::H> {
+        self.host().iter()
+    }
+
+    /// Iterate mutably over the elements of the host data of the tensor
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, T::H> {
+        self.host_mut().iter_mut()
+    }
+
+    /// Get a pointer to the host data of the tensor e.g. to pass it to a C library
+    ///
+    /// The pointer is valid for [`Tensor::len`] elements as long as the tensor is alive and not
+    /// resized. Panics if the tensor is not of type `T::H`
+    pub fn as_ptr(&self) -> *const T::H {
+        self.host().as_ptr()
+    }
+
+    /// Get a mutable pointer to the host data of the tensor e.g. to pass it to a C library
+    ///
+    /// The pointer is valid for [`Tensor::len`] elements as long as the tensor is alive and not
+    /// resized. Panics if the tensor is not of type `T::H`
+    pub fn as_mut_ptr(&mut self) -> *mut T::H {
+        self.host_mut().as_mut_ptr()
+    }
+
+    /// The number of elements in the host data of the tensor, same as [`Tensor::element_size`]
+    pub fn len(&self) -> usize {
+        self.element_size()
+    }
+
+    /// Returns true if the tensor has no elements
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reinterpret the host data of the tensor as another type of the same size
+    ///
+    /// This is useful for bit level inspection e.g. viewing a `f32` tensor as `u32`.
+    /// Fails if the size of `U` is not the same as the size of the element type of the tensor
+    pub fn view_as<U: HalideType>(&self) -> Result<Tensor<Ref<'_, Host<U>>>> {
+        ensure!(
+            core::mem::size_of::<U>() == core::mem::size_of::<T::H>(),
+            ErrorKind::SizeMismatch {
+                expected: core::mem::size_of::<T::H>(),
+                got: core::mem::size_of::<U>(),
+            };
+            format!(
+                "Cannot view a tensor of {} as {}",
+                std::any::type_name::<T::H>(),
+                std::any::type_name::<U>()
+            )
+        );
+        let data = self.try_host()?;
+        let shape = self.shape();
+        let tensor = unsafe {
+            Tensor_createWith(
+                shape.shape.as_ptr(),
+                shape.size,
+                halide_type_of::<U>(),
+                data.as_ptr().cast_mut().cast(),
+                self.get_dimension_type().to_mnn_sys(),
+            )
+        };
+        debug_assert!(!tensor.is_null());
+        Ok(Tensor {
+            tensor,
+            owns_object: true,
+            __marker: PhantomData,
+        })
+    }
+
+    /// Borrow the `index`-th element of the batch (first) dimension without copying
+    ///
+    /// The view keeps the batch dimension with a size of 1, e.g. slicing a `[2, 3]` tensor gives
+    /// a `[1, 3]` view over the second half of the buffer.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of range of the batch dimension, if the tensor is a scalar or if
+    /// it uses [`DimensionType::CaffeC4`] whose packed layout can't be sliced per batch.
+    pub fn batch_slice(&self, index: u32) -> Tensor<Ref<'_, Host<T::H>>> {
+        let dm_type = self.get_dimension_type();
+        assert!(
+            dm_type != DimensionType::CaffeC4,
+            "Cannot slice a tensor with the CaffeC4 dimension type"
+        );
+        let mut shape = self.shape();
+        assert!(shape.size > 0, "Cannot slice a scalar tensor");
+        let batch = shape[0].max(0) as usize;
+        let index = index as usize;
+        assert!(
+            index < batch,
+            "Batch index {index} out of range for a tensor of shape {shape:?}"
+        );
+        let data = self.host();
+        let stride = data.len() / batch;
+        let data = &data[index * stride..(index + 1) * stride];
+        shape[0] = 1;
+        let tensor = unsafe {
+            Tensor_createWith(
+                shape.shape.as_ptr(),
+                shape.size,
+                halide_type_of::<T::H>(),
+                data.as_ptr().cast_mut().cast(),
+                dm_type.to_mnn_sys(),
+            )
+        };
+        debug_assert!(!tensor.is_null());
+        Tensor {
+            tensor,
+            owns_object: true,
+            __marker: PhantomData,
+        }
+    }
+
+    /// Reinterpret the host buffer of the tensor as a slice of `U` without copying
+    ///
+    /// The length of the slice is the size of the buffer in bytes divided by the size of `U`,
+    /// any trailing bytes are not part of the slice. Unlike [`Tensor::view_as`] the element
+    /// type of the tensor is not checked, so this also works for tensors filled with raw bytes
+    /// e.g. through [`RawTensor::unchecked_host_bytes`](crate::RawTensor::unchecked_host_bytes).
+    ///
+    /// # Safety
+    /// - The host buffer must be properly aligned for `U`, which MNN's allocator guarantees for
+    ///   all the halide types but a borrowed buffer might not be
+    /// - The bytes of the buffer must be valid values of `U`, which holds for the integer and
+    ///   floating point types but not e.g. for `bool`
+    /// - The tensor must have host memory, i.e. not be a device tensor on a gpu backend
+    pub unsafe fn as_slice_of<U: HalideType>(&self) -> &[U] {
+        let data = unsafe { mnn_sys::Tensor_host(self.tensor) };
+        debug_assert!(!data.is_null());
+        debug_assert!(data.cast::<U>().is_aligned());
+        unsafe { core::slice::from_raw_parts(data.cast(), self.size() / core::mem::size_of::<U>()) }
+    }
+}
+
+impl<'t, T: HostTensorType> IntoIterator for &'t Tensor<T>
+where
+    T::H: HalideType,
+{
+    type Item = &'t T::H;
+    type IntoIter = core::slice::Iter<'t, T::H>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'t, T: HostTensorType> IntoIterator for &'t mut Tensor<T>
+where
+    T::H: HalideType,
+{
+    type Item = &'t mut T::H;
+    type IntoIter = core::slice::IterMut<'t, T::H>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Copies the host data of the tensor, fails if the tensor is not of type `T::H`
+impl<T: HostTensorType> TryFrom<&Tensor<T>> for Vec<T::H>
+where
+    T::H: HalideType + Clone,
+{
+    type Error = MNNError;
+    fn try_from(tensor: &Tensor<T>) -> Result<Self> {
+        Ok(tensor.try_host()?.to_vec())
+    }
+}
+
+/// Borrows the host data of the tensor, see [`Tensor::try_host`]
+impl<'t, T: HostTensorType> TryFrom<&'t Tensor<T>> for &'t [T::H]
+where
+    T::H: HalideType,
+{
+    type Error = MNNError;
+    fn try_from(tensor: &'t Tensor<T>) -> Result<Self> {
+        tensor.try_host()
+    }
+}
+
+/// Mutably borrows the host data of the tensor, see [`Tensor::try_host_mut`]
+impl<'t, T: HostTensorType> TryFrom<&'t mut Tensor<T>> for &'t mut [T::H]
+where
+    T::H: HalideType,
+{
+    type Error = MNNError;
+    fn try_from(tensor: &'t mut Tensor<T>) -> Result<Self> {
+        tensor.try_host_mut()
+    }
 }
 
 impl<T: DeviceTensorType> Tensor<T>
@@ -414,9 +1311,10 @@ where
     T::H: HalideType,
 {
     /// Try to wait for the device tensor to finish processing
-    pub fn wait(&self, map_type: MapType, finish: bool) {
+    /// Use [`TensorMapType::Read`] for output tensors and [`TensorMapType::Write`] for input tensors
+    pub fn wait(&self, map_type: impl Into<TensorMapType>, finish: bool) {
         unsafe {
-            Tensor_wait(self.tensor, map_type, finish as i32);
+            Tensor_wait(self.tensor, map_type.into().to_mnn_sys(), finish as i32);
         }
     }
 
@@ -463,9 +1361,33 @@ where
         debug_assert!(!tensor.is_null());
         Self {
             tensor,
+            owns_object: false,
             __marker: PhantomData,
         }
     }
+
+    /// Create a new tensor with every element set to zero
+    ///
+    /// Unlike [`Tensor::new`] the host memory is initialized, device tensors are filled through a
+    /// host tensor like [`Tensor::fill`]
+    pub fn zeros(shape: impl AsTensorShape, dm_type: DimensionType) -> Self
+    where
+        T::H: CastFrom<u8> + Copy,
+    {
+        let mut tensor = Self::new(shape, dm_type);
+        tensor.fill(T::H::cast_from(0));
+        tensor
+    }
+
+    /// Create a new tensor with every element set to one, see [`Tensor::zeros`]
+    pub fn ones(shape: impl AsTensorShape, dm_type: DimensionType) -> Self
+    where
+        T::H: CastFrom<u8> + Copy,
+    {
+        let mut tensor = Self::new(shape, dm_type);
+        tensor.fill(T::H::cast_from(1));
+        tensor
+    }
 }
 
 impl<T: OwnedTensorType> Clone for Tensor<T>
@@ -476,12 +1398,62 @@ where
         let tensor_ptr = unsafe { Tensor_clone(self.tensor) };
         Self {
             tensor: tensor_ptr,
+            owns_object: false,
             __marker: PhantomData,
         }
     }
 }
 
+/// The position of an element of a tensor in a host buffer with a given layout
+struct ChannelLayout {
+    batch: usize,
+    channel: usize,
+    spatial: usize,
+    dm_type: DimensionType,
+}
+
+impl ChannelLayout {
+    fn new(shape: &[i32], dm_type: DimensionType) -> Self {
+        let dims = shape.iter().map(|&d| d.max(0) as usize).collect::<Vec<_>>();
+        match (dims.as_slice(), dm_type) {
+            ([batch, rest @ .., channel], DimensionType::TensorFlow) => Self {
+                batch: *batch,
+                channel: *channel,
+                spatial: rest.iter().product(),
+                dm_type,
+            },
+            ([batch, channel, rest @ ..], _) => Self {
+                batch: *batch,
+                channel: *channel,
+                spatial: rest.iter().product(),
+                dm_type,
+            },
+            // Without a channel dimension all the layouts are the same
+            _ => Self {
+                batch: 1,
+                channel: 1,
+                spatial: dims.iter().product(),
+                dm_type: DimensionType::Caffe,
+            },
+        }
+    }
+
+    fn offset(&self, n: usize, c: usize, s: usize) -> usize {
+        match self.dm_type {
+            DimensionType::Caffe => (n * self.channel + c) * self.spatial + s,
+            DimensionType::TensorFlow => (n * self.spatial + s) * self.channel + c,
+            DimensionType::CaffeC4 => {
+                ((n * self.channel.div_ceil(4) + c / 4) * self.spatial + s) * 4 + c % 4
+            }
+        }
+    }
+}
+
 /// A tensor shape
+///
+/// Equality and hashing ignore trailing dimensions of size 1, so `[3]`, `[3, 1]` and
+/// `[3, 1, 1, 1]` are all considered the same shape since they describe the same data.
+/// Leading dimensions of size 1 are significant i.e. `[1, 3]` is not equal to `[3]`.
 #[derive(Clone, Copy)]
 #[repr(C)]
 pub struct TensorShape {
@@ -489,6 +1461,64 @@ pub struct TensorShape {
     pub(crate) size: usize,
 }
 
+impl TensorShape {
+    /// A 4 dimensional shape for a [`DimensionType::NCHW`] (or [`DimensionType::NC4HW4`]) tensor
+    pub const fn nchw(n: i32, c: i32, h: i32, w: i32) -> Self {
+        Self {
+            shape: [n, c, h, w],
+            size: 4,
+        }
+    }
+
+    /// A 4 dimensional shape for a [`DimensionType::NHWC`] tensor
+    pub const fn nhwc(n: i32, h: i32, w: i32, c: i32) -> Self {
+        Self {
+            shape: [n, h, w, c],
+            size: 4,
+        }
+    }
+
+    /// The dimensions of the shape without any trailing dimensions of size 1
+    fn logical_dims(&self) -> &[i32] {
+        let dims = &self.shape[..self.size];
+        let end = dims.iter().rposition(|&d| d != 1).map_or(0, |i| i + 1);
+        &dims[..end]
+    }
+
+    /// Compare two shapes ignoring trailing dimensions of size 1
+    ///
+    /// This is the same comparison used by the [PartialEq] implementation but it accepts any
+    /// array-like shape
+    pub fn logical_eq(&self, other: impl AsTensorShape) -> bool {
+        self.logical_dims() == other.as_tensor_shape().logical_dims()
+    }
+
+    /// The total number of elements of the shape or [`None`] if any dimension is dynamic (`-1`)
+    pub fn num_elements(&self) -> Option<usize> {
+        self.iter()
+            .try_fold(1usize, |acc, &dim| Some(acc * usize::try_from(dim).ok()?))
+    }
+
+    /// Whether none of the dimensions are dynamic (`-1`)
+    pub fn is_static(&self) -> bool {
+        !self.contains(&-1)
+    }
+}
+
+impl PartialEq for TensorShape {
+    fn eq(&self, other: &Self) -> bool {
+        self.logical_dims() == other.logical_dims()
+    }
+}
+
+impl Eq for TensorShape {}
+
+impl core::hash::Hash for TensorShape {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.logical_dims().hash(state);
+    }
+}
+
 impl From<mnn_sys::TensorShape> for TensorShape {
     fn from(value: mnn_sys::TensorShape) -> Self {
         Self {
@@ -541,6 +1571,25 @@ impl core::fmt::Debug for TensorShape {
     }
 }
 
+/// Formats the dimensions joined by `x`
+///
+/// ```
+/// use mnn::AsTensorShape;
+/// let shape = [1, 224, 224, 3].as_tensor_shape();
+/// assert_eq!(format!("{shape}"), "1x224x224x3");
+/// ```
+impl core::fmt::Display for TensorShape {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (i, dim) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str("x")?;
+            }
+            write!(f, "{dim}")?;
+        }
+        Ok(())
+    }
+}
+
 /// A trait to convert any array-like type to a tensor shape
 pub trait AsTensorShape {
     /// Convert the array-like type to a tensor shape
@@ -587,6 +1636,48 @@ mod as_tensor_shape_tests {
         shape_test!(&[i32], "small", &[1, 2, 3]);
         shape_test!(&[i32], "large", &[12, 23, 34, 45, 67]);
     }
+
+    #[test]
+    fn tensor_shape_num_elements() {
+        let shape = [2, 3].as_tensor_shape();
+        // The unused dimensions are padded with 1 and not counted
+        assert_eq!(shape.shape, [2, 3, 1, 1]);
+        assert_eq!(shape.num_elements(), Some(6));
+        assert!(shape.is_static());
+        assert_eq!(
+            [1, 3, 224, 224].as_tensor_shape().num_elements(),
+            Some(150528)
+        );
+        let dynamic = [-1, 3, 224, 224].as_tensor_shape();
+        assert_eq!(dynamic.num_elements(), None);
+        assert!(!dynamic.is_static());
+    }
+
+    #[test]
+    fn tensor_shape_nchw_nhwc() {
+        use super::TensorShape;
+        let nchw = TensorShape::nchw(1, 3, 224, 112);
+        assert_eq!(nchw.shape, [1, 3, 224, 112]);
+        assert_eq!(nchw.size, 4);
+        assert_eq!(nchw.num_elements(), Some(75264));
+        let nhwc = TensorShape::nhwc(2, 224, 112, 3);
+        assert_eq!(nhwc.shape, [2, 224, 112, 3]);
+        assert_eq!(nhwc.size, 4);
+        assert_eq!(nhwc.num_elements(), Some(150528));
+        assert_eq!(TensorShape::nchw(1, 1, 1, 1).num_elements(), Some(1));
+    }
+
+    #[test]
+    fn tensor_shape_eq_ignores_trailing_ones() {
+        use std::hash::{BuildHasher, RandomState};
+        let a = [3].as_tensor_shape();
+        let b = [3, 1, 1].as_tensor_shape();
+        assert_eq!(a, b);
+        assert!(a.logical_eq([3, 1, 1, 1]));
+        assert_ne!(a, [1, 3].as_tensor_shape());
+        let state = RandomState::new();
+        assert_eq!(state.hash_one(a), state.hash_one(b));
+    }
 }
 
 #[cfg(test)]
@@ -620,6 +1711,42 @@ where
         debug_assert!(!tensor.is_null());
         Self {
             tensor,
+            owns_object: true,
+            __marker: PhantomData,
+        }
+    }
+
+    /// Create a ref tensor over an externally allocated buffer
+    ///
+    /// This allows placing the tensor data in memory managed by a custom allocator e.g. a fixed
+    /// memory arena. The tensor never frees `data`, the caller retains ownership of the buffer and
+    /// is responsible for freeing it after the tensor is dropped.
+    ///
+    /// # Safety
+    /// `data` must be non-null, properly aligned for `T::H` and valid for reads (and writes for
+    /// [`RefMut`] tensors) of as many elements as the shape describes for the lifetime of the
+    /// returned tensor. The buffer must not be accessed through other pointers while the tensor
+    /// is alive.
+    pub unsafe fn from_raw_parts(
+        shape: impl AsTensorShape,
+        data: *mut T::H,
+        dm_type: DimensionType,
+    ) -> Self {
+        assert!(!data.is_null());
+        let shape = shape.as_tensor_shape();
+        let tensor = unsafe {
+            Tensor_createWith(
+                shape.shape.as_ptr(),
+                shape.size,
+                halide_type_of::<T::H>(),
+                data.cast(),
+                dm_type.to_mnn_sys(),
+            )
+        };
+        debug_assert!(!tensor.is_null());
+        Self {
+            tensor,
+            owns_object: true,
             __marker: PhantomData,
         }
     }
@@ -640,6 +1767,7 @@ where
         debug_assert!(!tensor.is_null());
         Self {
             tensor,
+            owns_object: true,
             __marker: PhantomData,
         }
     }
@@ -654,6 +1782,188 @@ fn test_tensor_borrowed() {
     assert_eq!(tensor.host(), data.as_slice());
 }
 
+#[test]
+fn test_tensor_zeros_ones() {
+    let zeros = Tensor::<Host<f32>>::zeros([2, 3], DimensionType::Caffe);
+    assert_eq!(zeros.shape().as_ref(), &[2, 3]);
+    assert!(zeros.host().iter().all(|&v| v == 0.0));
+    let ones = Tensor::<Host<i32>>::ones([2, 3], DimensionType::TensorFlow);
+    assert_eq!(ones.host(), &[1; 6]);
+}
+
+#[test]
+fn test_tensor_batch_slice() {
+    let tensor =
+        Tensor::<Host<i32>>::new_with_data([2, 3], DimensionType::Caffe, &[0, 1, 2, 3, 4, 5]);
+    let slice = tensor.batch_slice(1);
+    assert_eq!(slice.shape().as_ref(), &[1, 3]);
+    assert_eq!(slice.host(), &[3, 4, 5]);
+    assert_eq!(tensor.batch_slice(0).host(), &[0, 1, 2]);
+}
+
+#[test]
+#[should_panic = "out of range"]
+fn test_tensor_batch_slice_out_of_range() {
+    let tensor = Tensor::<Host<i32>>::new([2, 3], DimensionType::Caffe);
+    tensor.batch_slice(2);
+}
+
+#[test]
+fn test_tensor_from_raw_parts() {
+    let mut data = vec![0f32; 6];
+    let mut tensor = unsafe {
+        Tensor::<RefMut<Host<f32>>>::from_raw_parts([2, 3], data.as_mut_ptr(), DimensionType::NHWC)
+    };
+    assert_eq!(tensor.get_dimension_type(), DimensionType::NHWC);
+    tensor.host_mut().fill(2.0);
+    drop(tensor);
+    assert_eq!(data, &[2.0; 6]);
+}
+
+#[test]
+fn test_tensor_as_ptr() {
+    let mut tensor = Tensor::<Host<i32>>::new([2, 2], DimensionType::Caffe);
+    tensor.fill(7);
+    assert_eq!(tensor.len(), 4);
+    assert!(!tensor.is_empty());
+    let data = unsafe { core::slice::from_raw_parts(tensor.as_ptr(), tensor.len()) };
+    assert_eq!(data, &[7; 4]);
+    unsafe { *tensor.as_mut_ptr().add(3) = 1 };
+    assert_eq!(tensor.host(), &[7, 7, 7, 1]);
+}
+
+#[test]
+fn test_tensor_conversions() {
+    let mut tensor = Tensor::<Host<i32>>::new([2, 2], DimensionType::Caffe);
+    tensor.fill(3);
+    assert_eq!(Vec::try_from(&tensor).unwrap(), vec![3; 4]);
+    assert_eq!(<&[i32]>::try_from(&tensor).unwrap(), &[3; 4]);
+    <&mut [i32]>::try_from(&mut tensor).unwrap()[0] = 1;
+    assert_eq!(tensor.host(), &[1, 3, 3, 3]);
+    let view = tensor.view_as::<u32>().unwrap();
+    assert_eq!(Vec::try_from(&view).unwrap(), vec![1u32, 3, 3, 3]);
+    let mismatched: Tensor<Ref<'_, Host<f32>>> = unsafe { Tensor::from_ptr(tensor.tensor) };
+    assert!(Vec::try_from(&mismatched).is_err());
+    assert!(<&[f32]>::try_from(&mismatched).is_err());
+}
+
+#[test]
+fn test_tensor_new_with_data() {
+    let tensor = {
+        let data = vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+        Tensor::<Host<f32>>::new_with_data([3, 2], DimensionType::TensorFlow, &data)
+    };
+    assert_eq!(tensor.shape().as_ref(), &[3, 2]);
+    assert_eq!(tensor.get_dimension_type(), DimensionType::TensorFlow);
+    assert_eq!(tensor.host(), &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+}
+
+#[test]
+#[should_panic]
+fn test_tensor_new_with_data_size_mismatch() {
+    Tensor::<Host<f32>>::new_with_data([3, 2], DimensionType::Caffe, &[1.0; 5]);
+}
+
+#[test]
+fn test_tensor_reshape() {
+    let mut tensor = Tensor::<Host<i32>>::from_iter_with_shape([1, 2, 3], 0..6).unwrap();
+    tensor.reshape([6]).unwrap();
+    assert_eq!(tensor.shape().as_ref(), &[6]);
+    assert_eq!(tensor.host(), &[0, 1, 2, 3, 4, 5]);
+    tensor.reshape([3, 2]).unwrap();
+    assert_eq!(tensor.shape().as_ref(), &[3, 2]);
+    assert_eq!(tensor.host(), &[0, 1, 2, 3, 4, 5]);
+    assert!(tensor.reshape([4]).is_err());
+    assert_eq!(tensor.shape().as_ref(), &[3, 2]);
+}
+
+#[test]
+fn test_tensor_from_iter_with_shape() {
+    let tensor = Tensor::<Host<i32>>::from_iter_with_shape([2, 3], 0..6).unwrap();
+    assert_eq!(tensor.shape().as_ref(), &[2, 3]);
+    assert_eq!(tensor.host(), &[0, 1, 2, 3, 4, 5]);
+    let got = |iter: core::ops::Range<i32>| {
+        let Err(error) = Tensor::<Host<i32>>::from_iter_with_shape([2, 3], iter) else {
+            panic!("Expected a size mismatch");
+        };
+        match error.into_inner().current_context() {
+            ErrorKind::SizeMismatch { expected: 6, got } => *got,
+            kind => panic!("Unexpected error {kind:?}"),
+        }
+    };
+    assert_eq!(got(0..5), 5);
+    assert_eq!(got(0..9), 9);
+}
+
+#[test]
+fn test_tensor_batch_dim() {
+    let tensor = Tensor::<Host<i32>>::from_iter_with_shape([2, 3], 0..6).unwrap();
+    let batched = tensor.with_batch_dim().unwrap();
+    assert_eq!(batched.shape().as_ref(), &[1, 2, 3]);
+    assert_eq!(batched.host(), tensor.host());
+    let unbatched = batched.without_batch_dim().unwrap();
+    assert_eq!(unbatched.shape().as_ref(), &[2, 3]);
+    assert_eq!(unbatched.host(), tensor.host());
+    assert!(tensor.without_batch_dim().is_err());
+    let full = Tensor::<Host<i32>>::new([1, 1, 2, 3], DimensionType::Caffe);
+    assert!(full.with_batch_dim().is_err());
+}
+
+#[test]
+fn test_tensor_outer_chunks() {
+    let tensor = Tensor::<Host<i32>>::from_iter_with_shape([5, 2], 0..10).unwrap();
+    let chunks = tensor.outer_chunks(2).collect::<Vec<_>>();
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(chunks[0].shape().as_ref(), &[2, 2]);
+    assert_eq!(chunks[1].host(), &[4, 5, 6, 7]);
+    assert_eq!(chunks[2].shape().as_ref(), &[1, 2]);
+    assert_eq!(chunks[2].host(), &[8, 9]);
+}
+
+#[test]
+fn test_tensor_logical_strides() {
+    let tensor = Tensor::<Host<f32>>::new([2, 3, 4, 5], DimensionType::Caffe);
+    assert!(tensor.is_contiguous());
+    assert_eq!(tensor.logical_strides(), vec![60, 20, 5, 1]);
+    let packed = Tensor::<Host<f32>>::new([2, 3, 4, 5], DimensionType::CaffeC4);
+    assert!(!packed.is_contiguous());
+    assert_eq!(packed.logical_strides(), vec![60, 20, 5, 1]);
+}
+
+#[test]
+fn test_dimension_type_strides() {
+    let nchw = DimensionType::NCHW
+        .strides(&TensorShape::nchw(1, 3, 4, 4))
+        .unwrap();
+    assert_eq!(nchw, [48, 16, 4, 1]);
+    let nhwc = DimensionType::NHWC
+        .strides(&TensorShape::nhwc(1, 4, 4, 3))
+        .unwrap();
+    assert_eq!(nhwc, [48, 1, 12, 3]);
+
+    // The element at (n, c, h, w) = (0, 2, 1, 3) in both layouts
+    let tensor = Tensor::<Host<f32>>::new_with_data(
+        TensorShape::nchw(1, 3, 4, 4),
+        DimensionType::NCHW,
+        &(0..48).map(|i| i as f32).collect::<Vec<_>>(),
+    );
+    let converted = tensor.to_dimension_type(DimensionType::NHWC).unwrap();
+    let offset = |s: [i32; 4]| (2 * s[1] + s[2] + 3 * s[3]) as usize;
+    assert_eq!(tensor.host()[offset(nchw)], 39.0);
+    assert_eq!(converted.host()[offset(nhwc)], 39.0);
+
+    assert!(
+        DimensionType::NC4HW4
+            .strides(&TensorShape::nchw(1, 3, 4, 4))
+            .is_err()
+    );
+    assert!(
+        DimensionType::NCHW
+            .strides(&[2, 3].as_tensor_shape())
+            .is_err()
+    );
+}
+
 #[test]
 fn test_tensor_borrow_mut() {
     let shape = [1, 2, 3];
@@ -662,3 +1972,197 @@ fn test_tensor_borrow_mut() {
     tensor.host_mut().fill(1);
     assert_eq!(data, &[1, 1, 1, 1, 1, 1]);
 }
+
+#[test]
+fn test_tensor_view_as() {
+    let data = vec![1.0f32, -0.0, f32::INFINITY];
+    let tensor = Tensor::<Ref<Host<f32>>>::borrowed([3], &data);
+    let bits = tensor.view_as::<u32>().unwrap();
+    assert_eq!(
+        bits.host(),
+        data.iter().map(|v| v.to_bits()).collect::<Vec<_>>()
+    );
+    assert!(tensor.view_as::<u8>().is_err());
+}
+
+#[test]
+fn test_tensor_as_slice_of() {
+    let tensor = Tensor::<Host<i8>>::from_iter_with_shape([4], [-1, 0, 1, 127]).unwrap();
+    let bytes = unsafe { tensor.as_slice_of::<u8>() };
+    assert_eq!(bytes, &[255, 0, 1, 127]);
+    let tensor = Tensor::<Host<i8>>::from_iter_with_shape([2, 3], 0..6).unwrap();
+    // Trailing bytes that don't make up a whole element are left out
+    assert_eq!(unsafe { tensor.as_slice_of::<u16>() }.len(), 3);
+}
+
+#[test]
+fn test_tensor_iter() {
+    let mut tensor = Tensor::<Host<i32>>::from_iter_with_shape([2, 3], 0..6).unwrap();
+    assert_eq!(tensor.iter().sum::<i32>(), 15);
+    tensor.iter_mut().for_each(|x| *x *= 2);
+    for x in &mut tensor {
+        *x += 1;
+    }
+    assert_eq!(
+        (&tensor).into_iter().copied().collect::<Vec<_>>(),
+        [1, 3, 5, 7, 9, 11]
+    );
+}
+
+#[test]
+fn test_tensor_fill_with() {
+    let mut tensor = Tensor::<Host<f32>>::new([2, 3], DimensionType::Caffe);
+    tensor.fill_with(|i| i as f32);
+    assert_eq!(tensor.host(), &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+}
+
+#[test]
+fn test_tensor_try_into_vec() {
+    let tensor = Tensor::<Host<i32>>::from_iter_with_shape([2, 3], 0..6).unwrap();
+    assert_eq!(tensor.try_into_vec().unwrap(), [0, 1, 2, 3, 4, 5]);
+    let view = tensor.view_as::<u32>().unwrap();
+    assert_eq!(view.try_into_vec().unwrap(), [0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_tensor_copy_from_slice() {
+    let mut tensor = Tensor::<Host<f32>>::new([2, 2], DimensionType::Caffe);
+    tensor.copy_from_slice(&[1.0, 2.0, 3.0, 4.0]).unwrap();
+    assert_eq!(tensor.host(), &[1.0, 2.0, 3.0, 4.0]);
+    let error = tensor.copy_from_slice(&[1.0, 2.0, 3.0]).unwrap_err();
+    assert!(matches!(
+        error.into_inner().current_context(),
+        ErrorKind::SizeMismatch {
+            expected: 4,
+            got: 3
+        }
+    ));
+    assert_eq!(tensor.host(), &[1.0, 2.0, 3.0, 4.0]);
+}
+
+#[test]
+fn test_tensor_to_dimension_type() {
+    let data = (0..10).map(|i| i as f32).collect::<Vec<_>>();
+    let nchw = Tensor::<Host<f32>>::new_with_data([1, 5, 2, 1], DimensionType::NCHW, &data);
+    let nc4hw4 = nchw.to_dimension_type(DimensionType::NC4HW4).unwrap();
+    assert_eq!(nc4hw4.shape().as_ref(), &[1, 5, 2, 1]);
+    // Two blocks of 4 channels for each of the 2 pixels, the last block is padded
+    let packed = unsafe { nc4hw4.as_slice_of::<f32>() };
+    assert_eq!(packed.len(), 16);
+    assert_eq!(&packed[..8], &[0.0, 2.0, 4.0, 6.0, 1.0, 3.0, 5.0, 7.0]);
+    assert_eq!((packed[8], packed[12]), (8.0, 9.0));
+
+    let back = nc4hw4.to_dimension_type(DimensionType::NCHW).unwrap();
+    assert_eq!(back.host(), data.as_slice());
+    let nhwc = nc4hw4.to_dimension_type(DimensionType::NHWC).unwrap();
+    assert_eq!(nhwc.shape().as_ref(), &[1, 2, 1, 5]);
+    assert_eq!(
+        nhwc.host(),
+        &[0.0, 2.0, 4.0, 6.0, 8.0, 1.0, 3.0, 5.0, 7.0, 9.0]
+    );
+}
+
+#[test]
+fn test_tensor_dtype_name() {
+    fn name_of<H: HalideType>() -> &'static str {
+        Tensor::<Host<H>>::new([1], DimensionType::Caffe).dtype_name()
+    }
+    assert_eq!(name_of::<bool>(), "bool");
+    assert_eq!(name_of::<u8>(), "u8");
+    assert_eq!(name_of::<u16>(), "u16");
+    assert_eq!(name_of::<u32>(), "u32");
+    assert_eq!(name_of::<u64>(), "u64");
+    assert_eq!(name_of::<i8>(), "i8");
+    assert_eq!(name_of::<i16>(), "i16");
+    assert_eq!(name_of::<i32>(), "i32");
+    assert_eq!(name_of::<i64>(), "i64");
+    assert_eq!(name_of::<f32>(), "f32");
+    assert_eq!(name_of::<f64>(), "f64");
+
+    use mnn_sys::halide_type_code_t::*;
+    let name = |code: mnn_sys::halide_type_code_t, bits| {
+        TensorDataType::from_parts(code as u8, bits, 1).name()
+    };
+    assert_eq!(name(halide_type_float, 16), "f16");
+    assert_eq!(name(halide_type_bfloat, 16), "bf16");
+    assert_eq!(name(halide_type_handle, 64), "handle");
+    assert_eq!(name(halide_type_float, 8), "unknown");
+
+    let tensor = Tensor::<Host<u8>>::new([2], DimensionType::Caffe);
+    let mistyped = unsafe { Tensor::<Ref<'_, Host<i32>>>::from_ptr(tensor.tensor) };
+    let error = mistyped.try_host().unwrap_err();
+    assert!(error.to_string().contains("The tensor holds u8"));
+    assert!(matches!(
+        error.into_inner().current_context(),
+        ErrorKind::HalideTypeMismatch { got: "i32" }
+    ));
+}
+
+#[test]
+fn test_tensor_eq() {
+    let a = Tensor::<Host<i32>>::from_iter_with_shape([2, 3], 0..6).unwrap();
+    let b = Tensor::<Host<i32>>::from_iter_with_shape([2, 3], 0..6).unwrap();
+    assert!(a == b);
+    let c = Tensor::<Host<i32>>::from_iter_with_shape([3, 2], 0..6).unwrap();
+    assert!(a != c);
+    let d = Tensor::<Host<i32>>::from_iter_with_shape([2, 3], 1..7).unwrap();
+    assert!(a != d);
+    assert!(a.approx_eq(&d, 1));
+    assert!(!a.approx_eq(&d, 0));
+}
+
+#[test]
+fn test_tensor_approx_eq() {
+    let a = Tensor::<Host<f32>>::new_with_data([3], DimensionType::Caffe, &[1.0, 2.0, 3.0]);
+    let b = Tensor::<Host<f32>>::new_with_data([3], DimensionType::Caffe, &[1.0, 2.0005, 2.9995]);
+    assert!(a != b);
+    assert!(a.approx_eq(&b, 1e-3));
+    assert!(!a.approx_eq(&b, 1e-4));
+    let nan = Tensor::<Host<f32>>::new_with_data([3], DimensionType::Caffe, &[1.0, 2.0, f32::NAN]);
+    assert!(!nan.approx_eq(&nan, 1.0));
+}
+
+#[test]
+fn test_tensor_try_fill() {
+    let mut tensor = Tensor::<Host<u8>>::new([2, 2], DimensionType::Caffe);
+    tensor.try_fill(7).unwrap();
+    assert_eq!(tensor.host(), &[7, 7, 7, 7]);
+
+    let mut mistyped = unsafe { Tensor::<RefMut<'_, Host<i32>>>::from_ptr(tensor.tensor) };
+    let error = mistyped.try_fill(1).unwrap_err();
+    assert!(matches!(
+        error.into_inner().current_context(),
+        ErrorKind::HalideTypeMismatch { got: "i32", .. }
+    ));
+    assert_eq!(tensor.host(), &[7, 7, 7, 7]);
+}
+
+#[test]
+fn test_dimension_type_from_str() {
+    for dm in [
+        DimensionType::Caffe,
+        DimensionType::CaffeC4,
+        DimensionType::TensorFlow,
+    ] {
+        assert_eq!(dm.to_string().parse::<DimensionType>().unwrap(), dm);
+    }
+    assert_eq!(
+        "TensorFlow".parse::<DimensionType>().unwrap(),
+        DimensionType::NHWC
+    );
+    assert_eq!(
+        "caffe_c4".parse::<DimensionType>().unwrap(),
+        DimensionType::NC4HW4
+    );
+    for (alias, dm) in [
+        ("NCHW", DimensionType::Caffe),
+        ("Caffe", DimensionType::Caffe),
+        ("NHWC", DimensionType::TensorFlow),
+        ("tf", DimensionType::TensorFlow),
+        ("NC4HW4", DimensionType::CaffeC4),
+        ("CaffeC4", DimensionType::CaffeC4),
+    ] {
+        assert_eq!(alias.parse::<DimensionType>().unwrap(), dm, "{alias}");
+    }
+    assert!("hwc".parse::<DimensionType>().is_err());
+}
@@ -0,0 +1,98 @@
+//! Decoding images into normalized input tensors for vision models
+use super::*;
+use std::path::Path;
+
+impl Tensor<Host<f32>> {
+    /// Decode the image at `path` into a normalized RGB tensor
+    ///
+    /// The image is converted to RGB and resized to `size` (width, height) if one is given.
+    /// Every pixel is scaled to `[0, 1]` and normalized per channel as `(x - mean[c]) / std[c]`
+    /// (e.g. `[0.485, 0.456, 0.406]` and `[0.229, 0.224, 0.225]` for models trained on imagenet).
+    ///
+    /// The shape is `[1, 3, h, w]` for [`DimensionType::Caffe`] and [`DimensionType::CaffeC4`]
+    /// and `[1, h, w, 3]` for [`DimensionType::TensorFlow`].
+    pub fn from_image(
+        path: impl AsRef<Path>,
+        layout: DimensionType,
+        size: Option<(u32, u32)>,
+        mean: [f32; 3],
+        std: [f32; 3],
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let image = ::image::open(path)
+            .change_context(ErrorKind::IOError)
+            .attach_printable_lazy(|| path.display().to_string())?;
+        let image = match size {
+            Some((width, height)) => {
+                image.resize_exact(width, height, ::image::imageops::FilterType::Triangle)
+            }
+            None => image,
+        }
+        .into_rgb8();
+
+        let (width, height) = image.dimensions();
+        let plane = width as usize * height as usize;
+        let mut data = vec![0f32; plane * 3];
+        image.pixels().enumerate().for_each(|(i, pixel)| {
+            pixel.0.iter().enumerate().for_each(|(c, &value)| {
+                let index = match layout {
+                    DimensionType::TensorFlow => i * 3 + c,
+                    DimensionType::Caffe | DimensionType::CaffeC4 => c * plane + i,
+                };
+                data[index] = (f32::from(value) / 255.0 - mean[c]) / std[c];
+            })
+        });
+
+        let (width, height) = (width as i32, height as i32);
+        match layout {
+            DimensionType::TensorFlow => Ok(Self::new_with_data(
+                TensorShape::nhwc(1, height, width, 3),
+                layout,
+                &data,
+            )),
+            DimensionType::Caffe => Ok(Self::new_with_data(
+                TensorShape::nchw(1, 3, height, width),
+                layout,
+                &data,
+            )),
+            DimensionType::CaffeC4 => Self::new_with_data(
+                TensorShape::nchw(1, 3, height, width),
+                DimensionType::Caffe,
+                &data,
+            )
+            .to_dimension_type(layout),
+        }
+    }
+}
+
+#[test]
+fn test_tensor_from_image() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/assets/rgb_2x2.png");
+    let nchw = Tensor::from_image(path, DimensionType::Caffe, None, [0.0; 3], [1.0; 3]).unwrap();
+    assert_eq!(nchw.shape().as_ref(), &[1, 3, 2, 2]);
+    // Pixels are red, green / blue, white
+    assert_eq!(
+        nchw.host(),
+        &[1., 0., 0., 1., 0., 1., 0., 1., 0., 0., 1., 1.]
+    );
+
+    let nhwc = Tensor::from_image(
+        path,
+        DimensionType::TensorFlow,
+        Some((1, 1)),
+        [0.5; 3],
+        [0.5; 3],
+    )
+    .unwrap();
+    assert_eq!(nhwc.shape().as_ref(), &[1, 1, 1, 3]);
+    assert_eq!(nhwc.host().len(), 3);
+
+    let missing = Tensor::from_image(
+        "does-not-exist.png",
+        DimensionType::Caffe,
+        None,
+        [0.0; 3],
+        [1.0; 3],
+    );
+    assert!(missing.is_err());
+}
@@ -0,0 +1,412 @@
+//! A small self describing binary format for host tensors
+//!
+//! The layout (all integers are little-endian) is
+//! | field     | type          |
+//! | --------- | ------------- |
+//! | magic     | `b"MNNT"`     |
+//! | version   | u16           |
+//! | code      | u8            |
+//! | bits      | u8            |
+//! | lanes     | u16           |
+//! | dim type  | u8            |
+//! | rank      | u8            |
+//! | dims      | i32 * rank    |
+//! | data      | little-endian |
+use super::*;
+use std::io::Write;
+use std::path::Path;
+
+const MAGIC: [u8; 4] = *b"MNNT";
+const VERSION: u16 = 1;
+
+fn dimension_type_to_u8(dm: DimensionType) -> u8 {
+    match dm {
+        DimensionType::TensorFlow => 0,
+        DimensionType::Caffe => 1,
+        DimensionType::CaffeC4 => 2,
+    }
+}
+
+fn dimension_type_from_u8(dm: u8) -> Result<DimensionType> {
+    match dm {
+        0 => Ok(DimensionType::TensorFlow),
+        1 => Ok(DimensionType::Caffe),
+        2 => Ok(DimensionType::CaffeC4),
+        _ => Err(error!(ErrorKind::ParseError)
+            .attach_printable(format!("Invalid dimension type {dm} in tensor header"))),
+    }
+}
+
+/// Swap the bytes of every element in place if the target is big-endian
+fn to_from_le<H>(bytes: &mut [u8]) {
+    if cfg!(target_endian = "big") && core::mem::size_of::<H>() > 1 {
+        bytes
+            .chunks_exact_mut(core::mem::size_of::<H>())
+            .for_each(|element| element.reverse());
+    }
+}
+
+/// Copy the elements into a byte vector in little-endian order
+fn to_le_bytes<H>(data: &[H]) -> Vec<u8> {
+    let mut bytes = unsafe {
+        core::slice::from_raw_parts(data.as_ptr().cast::<u8>(), core::mem::size_of_val(data))
+    }
+    .to_vec();
+    to_from_le::<H>(&mut bytes);
+    bytes
+}
+
+/// 64 bit FNV-1a hash, used since it's trivial and stable across platforms and releases unlike
+/// [`std::hash::DefaultHasher`]
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0 = bytes.iter().fold(self.0, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(Self::PRIME)
+        });
+    }
+}
+
+/// Split off the first `n` bytes of the cursor
+fn take<'a>(cursor: &mut &'a [u8], n: usize) -> Result<&'a [u8]> {
+    ensure!(cursor.len() >= n, ErrorKind::ParseError; "Unexpected end of tensor file");
+    let (head, tail) = cursor.split_at(n);
+    *cursor = tail;
+    Ok(head)
+}
+
+fn take_array<const N: usize>(cursor: &mut &[u8]) -> Result<[u8; N]> {
+    Ok(take(cursor, N)?
+        .try_into()
+        .expect("Slice is exactly N bytes"))
+}
+
+impl<H: HalideType> Tensor<Host<H>> {
+    /// Save the tensor to a file with a small header containing the shape and data type followed
+    /// by the raw little-endian data
+    ///
+    /// Use [`Tensor::load_raw`] to read it back. The data is always saved row-major,
+    /// [`DimensionType::CaffeC4`] tensors are unpacked and saved as [`DimensionType::Caffe`].
+    pub fn save_raw(&self, path: impl AsRef<Path>) -> Result<()>
+    where
+        H: Copy,
+    {
+        let path = path.as_ref();
+        let unpacked;
+        let tensor = if self.get_dimension_type() == DimensionType::CaffeC4 {
+            unpacked = self.to_dimension_type(DimensionType::Caffe)?;
+            &unpacked
+        } else {
+            self
+        };
+        let shape = tensor.shape();
+        let htype = halide_type_of::<H>();
+        let bytes = to_le_bytes(tensor.try_host()?);
+
+        let mut header = Vec::with_capacity(12 + shape.len() * 4);
+        header.extend_from_slice(&MAGIC);
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.push(htype.code as u8);
+        header.push(htype.bits);
+        header.extend_from_slice(&htype.lanes.to_le_bytes());
+        header.push(dimension_type_to_u8(tensor.get_dimension_type()));
+        header.push(shape.len() as u8);
+        shape
+            .iter()
+            .for_each(|dim| header.extend_from_slice(&dim.to_le_bytes()));
+
+        let file = std::fs::File::create(path)
+            .change_context(ErrorKind::IOError)
+            .attach_printable_lazy(|| path.display().to_string())?;
+        let mut writer = std::io::BufWriter::new(file);
+        writer
+            .write_all(&header)
+            .and_then(|_| writer.write_all(&bytes))
+            .and_then(|_| writer.flush())
+            .change_context(ErrorKind::IOError)
+            .attach_printable_lazy(|| path.display().to_string())?;
+        Ok(())
+    }
+
+    /// Compute a deterministic hash of the shape, data type and data of the tensor
+    ///
+    /// The data is hashed in little-endian order so the checksum is stable across runs and
+    /// platforms, this makes it useful to cheaply detect changes in model outputs without storing
+    /// the full tensors. [`DimensionType::CaffeC4`] tensors are unpacked first so they have the
+    /// same checksum as the [`DimensionType::Caffe`] tensor with the same data.
+    ///
+    /// Fails if the tensor is not of type `H`
+    pub fn checksum(&self) -> Result<u64>
+    where
+        H: Copy,
+    {
+        let unpacked;
+        let tensor = if self.get_dimension_type() == DimensionType::CaffeC4 {
+            unpacked = self.to_dimension_type(DimensionType::Caffe)?;
+            &unpacked
+        } else {
+            self
+        };
+        let shape = tensor.shape();
+        let htype = halide_type_of::<H>();
+        let mut hasher = Fnv1a::new();
+        hasher.write(&[htype.code as u8, htype.bits]);
+        hasher.write(&htype.lanes.to_le_bytes());
+        hasher.write(&[shape.len() as u8]);
+        shape
+            .iter()
+            .for_each(|dim| hasher.write(&dim.to_le_bytes()));
+        hasher.write(&to_le_bytes(tensor.try_host()?));
+        Ok(hasher.0)
+    }
+
+    /// Load a tensor saved with [`Tensor::save_raw`]
+    ///
+    /// Fails if the data type of the file doesn't match `H`, if the data doesn't match the
+    /// shape in the header or if the header claims the row-major data is
+    /// [`DimensionType::CaffeC4`], convert the tensor with [`Tensor::to_dimension_type`] after
+    /// loading it instead
+    pub fn load_raw(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .change_context(ErrorKind::IOError)
+            .attach_printable_lazy(|| path.display().to_string())?;
+        let mut cursor = bytes.as_slice();
+
+        ensure!(
+            take(&mut cursor, 4)? == MAGIC,
+            ErrorKind::ParseError;
+            "Not a tensor file (invalid magic)"
+        );
+        let version = u16::from_le_bytes(take_array(&mut cursor)?);
+        ensure!(
+            version <= VERSION,
+            ErrorKind::ParseError;
+            format!("Unsupported tensor file version {version}, expected at most {VERSION}")
+        );
+        let [code, bits] = take_array(&mut cursor)?;
+        let lanes = u16::from_le_bytes(take_array(&mut cursor)?);
+        let htype = halide_type_of::<H>();
+        ensure!(
+            code == htype.code as u8 && bits == htype.bits && lanes == htype.lanes,
+            ErrorKind::HalideTypeMismatch {
+                got: std::any::type_name::<H>(),
+            };
+            format!("File contains data with halide type code {code}, bits {bits}, lanes {lanes}"),
+            format!("The file holds {}", TensorDataType::from_parts(code, bits, lanes).name())
+        );
+        let [dm_type, rank] = take_array(&mut cursor)?;
+        let dm_type = dimension_type_from_u8(dm_type)?;
+        ensure!(
+            dm_type != DimensionType::CaffeC4,
+            ErrorKind::ParseError;
+            "Cannot load a CaffeC4 tensor from row-major data"
+        );
+        ensure!(rank <= 4, ErrorKind::ParseError; format!("Unsupported tensor rank {rank}"));
+        let dims = (0..rank)
+            .map(|_| Ok(i32::from_le_bytes(take_array(&mut cursor)?)))
+            .collect::<Result<Vec<i32>>>()?;
+        let data = cursor;
+
+        let expected =
+            dims.iter().map(|&d| d.max(0) as usize).product::<usize>() * core::mem::size_of::<H>();
+        ensure!(
+            data.len() == expected,
+            ErrorKind::SizeMismatch {
+                expected,
+                got: data.len(),
+            }
+        );
+        let mut tensor = Self::new(dims, dm_type);
+        let host = tensor.try_host_mut()?;
+        let host = unsafe {
+            core::slice::from_raw_parts_mut(
+                host.as_mut_ptr().cast::<u8>(),
+                core::mem::size_of_val(host),
+            )
+        };
+        ensure!(
+            host.len() == data.len(),
+            ErrorKind::SizeMismatch {
+                expected: host.len(),
+                got: data.len(),
+            }
+        );
+        host.copy_from_slice(data);
+        to_from_le::<H>(host);
+        Ok(tensor)
+    }
+}
+
+/// Serialized as a struct with the shape, dimension type and data of the tensor
+///
+/// The data is always serialized row-major, [`DimensionType::CaffeC4`] tensors are unpacked and
+/// serialized as [`DimensionType::Caffe`].
+#[cfg(feature = "serde")]
+impl<H: HalideType + serde::Serialize + Copy> serde::Serialize for Tensor<Host<H>> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error as _, SerializeStruct as _};
+        let unpacked;
+        let tensor = if self.get_dimension_type() == DimensionType::CaffeC4 {
+            unpacked = self
+                .to_dimension_type(DimensionType::Caffe)
+                .map_err(|e| S::Error::custom(format!("{e:?}")))?;
+            &unpacked
+        } else {
+            self
+        };
+        let data = tensor
+            .try_host()
+            .map_err(|e| S::Error::custom(format!("{e:?}")))?;
+        let mut state = serializer.serialize_struct("Tensor", 3)?;
+        state.serialize_field("shape", tensor.shape().as_ref())?;
+        state.serialize_field("dim_type", &tensor.get_dimension_type())?;
+        state.serialize_field("data", data)?;
+        state.end()
+    }
+}
+
+/// Fails if the length of the data doesn't match the shape or the dimension type is
+/// [`DimensionType::CaffeC4`] since the serialized data is row-major, convert the tensor with
+/// [`Tensor::to_dimension_type`] after deserializing it instead
+#[cfg(feature = "serde")]
+impl<'de, H: HalideType + serde::Deserialize<'de>> serde::Deserialize<'de> for Tensor<Host<H>> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "Tensor")]
+        struct Repr<H> {
+            shape: Vec<i32>,
+            dim_type: DimensionType,
+            data: Vec<H>,
+        }
+        let repr = Repr::<H>::deserialize(deserializer)?;
+        if repr.dim_type == DimensionType::CaffeC4 {
+            return Err(D::Error::custom(
+                "Cannot deserialize a CaffeC4 tensor from row-major data",
+            ));
+        }
+        if repr.shape.len() > 4 || repr.shape.iter().any(|&dim| dim < 0) {
+            return Err(D::Error::custom(format!(
+                "Invalid tensor shape {:?}",
+                repr.shape
+            )));
+        }
+        let expected = repr
+            .shape
+            .iter()
+            .map(|&dim| dim as usize)
+            .product::<usize>();
+        if repr.data.len() != expected {
+            return Err(D::Error::invalid_length(
+                repr.data.len(),
+                &format!("{expected} elements for shape {:?}", repr.shape).as_str(),
+            ));
+        }
+        let mut tensor = Self::new(repr.shape, repr.dim_type);
+        tensor
+            .try_host_mut()
+            .map_err(|e| D::Error::custom(format!("{e:?}")))?
+            .iter_mut()
+            .zip(repr.data)
+            .for_each(|(dst, src)| *dst = src);
+        Ok(tensor)
+    }
+}
+
+#[test]
+fn test_tensor_save_load_raw() {
+    let path = std::env::temp_dir().join(format!("mnn-rs-save-raw-{}.bin", std::process::id()));
+    let mut tensor = Tensor::<Host<f32>>::new([1, 2, 3], DimensionType::Caffe);
+    tensor
+        .host_mut()
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, v)| *v = i as f32);
+    tensor.save_raw(&path).unwrap();
+    let loaded = Tensor::<Host<f32>>::load_raw(&path).unwrap();
+    assert_eq!(loaded.shape().as_ref(), &[1, 2, 3]);
+    assert_eq!(loaded.host(), tensor.host());
+    assert!(Tensor::<Host<i32>>::load_raw(&path).is_err());
+
+    // Packed tensors are saved unpacked
+    let tensor =
+        Tensor::<Host<f32>>::from_iter_with_shape([1, 2, 1, 2], (0..4).map(|i| i as f32)).unwrap();
+    let packed = tensor.to_dimension_type(DimensionType::CaffeC4).unwrap();
+    packed.save_raw(&path).unwrap();
+    let loaded = Tensor::<Host<f32>>::load_raw(&path).unwrap();
+    assert_eq!(loaded.get_dimension_type(), DimensionType::Caffe);
+    assert_eq!(loaded.shape().as_ref(), &[1, 2, 1, 2]);
+    assert_eq!(loaded.host(), tensor.host());
+
+    // A header claiming packed data is rejected
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[10] = dimension_type_to_u8(DimensionType::CaffeC4);
+    std::fs::write(&path, bytes).unwrap();
+    assert!(Tensor::<Host<f32>>::load_raw(&path).is_err());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_tensor_checksum() {
+    let mut tensor = Tensor::<Host<i32>>::new([2, 3], DimensionType::Caffe);
+    tensor
+        .host_mut()
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, v)| *v = i as i32);
+    let checksum = tensor.checksum().unwrap();
+    assert_eq!(checksum, tensor.clone().checksum().unwrap());
+    // Same data in a different shape
+    let reshaped = Tensor::<Host<i32>>::from_iter_with_shape([3, 2], 0..6).unwrap();
+    assert_eq!(reshaped.host(), tensor.host());
+    assert_ne!(checksum, reshaped.checksum().unwrap());
+    tensor.host_mut()[5] = 0;
+    assert_ne!(checksum, tensor.checksum().unwrap());
+
+    // Packed tensors hash like their unpacked data
+    let tensor = Tensor::<Host<i32>>::from_iter_with_shape([1, 5, 1, 2], 0..10).unwrap();
+    let packed = tensor.to_dimension_type(DimensionType::CaffeC4).unwrap();
+    assert_eq!(packed.checksum().unwrap(), tensor.checksum().unwrap());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_tensor_serde() {
+    let mut tensor = Tensor::<Host<f32>>::new([1, 2, 2], DimensionType::TensorFlow);
+    tensor
+        .host_mut()
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, v)| *v = i as f32);
+    let json = serde_json::to_string(&tensor).unwrap();
+    let loaded: Tensor<Host<f32>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.shape().as_ref(), &[1, 2, 2]);
+    assert_eq!(loaded.get_dimension_type(), DimensionType::TensorFlow);
+    assert_eq!(loaded.host(), tensor.host());
+    let invalid = r#"{"shape":[2,2],"dim_type":"Caffe","data":[1.0,2.0,3.0]}"#;
+    assert!(serde_json::from_str::<Tensor<Host<f32>>>(invalid).is_err());
+
+    let mut tensor = Tensor::<Host<f32>>::new([1, 2, 1, 2], DimensionType::Caffe);
+    tensor
+        .host_mut()
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, v)| *v = i as f32);
+    let packed = tensor.to_dimension_type(DimensionType::CaffeC4).unwrap();
+    let json = serde_json::to_string(&packed).unwrap();
+    let loaded: Tensor<Host<f32>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.get_dimension_type(), DimensionType::Caffe);
+    assert_eq!(loaded.shape().as_ref(), &[1, 2, 1, 2]);
+    assert_eq!(loaded.host(), tensor.host());
+    let packed = r#"{"shape":[1,1,1,1],"dim_type":"CaffeC4","data":[1.0]}"#;
+    assert!(serde_json::from_str::<Tensor<Host<f32>>>(packed).is_err());
+}
@@ -119,7 +119,8 @@ impl<'t, 'tl> TensorInfo<'t, 'tl> {
             tensor.is_type_of::<H>(),
             ErrorKind::HalideTypeMismatch {
                 got: std::any::type_name::<H>(),
-            }
+            };
+            format!("The tensor holds {}", tensor.dtype_name())
         );
         Ok(tensor)
     }
@@ -134,7 +135,8 @@ impl<'t, 'tl> TensorInfo<'t, 'tl> {
             tensor.is_type_of::<H>(),
             ErrorKind::HalideTypeMismatch {
                 got: std::any::type_name::<H>(),
-            }
+            };
+            format!("The tensor holds {}", tensor.dtype_name())
         );
         Ok(tensor)
     }
@@ -0,0 +1,265 @@
+//! Small post-processing operations on host tensors
+use super::*;
+
+/// Split the shape around `axis` into the (outer, axis, inner) number of elements
+fn split_axis(shape: &TensorShape, axis: usize) -> Result<(usize, usize, usize)> {
+    ensure!(
+        axis < shape.len(),
+        ErrorKind::TensorError;
+        format!("Axis {axis} is out of range for a tensor of shape {shape:?}")
+    );
+    let dim = |d: &i32| (*d).max(0) as usize;
+    let outer = shape.iter().take(axis).map(dim).product();
+    let inner = shape.iter().skip(axis + 1).map(dim).product();
+    Ok((outer, dim(&shape[axis]), inner))
+}
+
+/// Numeric conversion between the element types of tensors with the semantics of `as`
+pub trait CastFrom<T>: Sized {
+    /// Convert `value` like `value as Self`
+    fn cast_from(value: T) -> Self;
+}
+
+macro_rules! cast_from {
+    ($($t:ty),*) => {
+        cast_from!(@outer [$($t),*] $($t),*);
+    };
+    (@outer $all:tt $($t:ty),*) => {
+        $(cast_from!(@inner $t $all);)*
+    };
+    (@inner $t:ty [$($from:ty),*]) => {
+        $(
+            impl CastFrom<$from> for $t {
+                #[inline]
+                fn cast_from(value: $from) -> Self {
+                    value as $t
+                }
+            }
+        )*
+    };
+}
+
+cast_from!(f32, f64, u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl<T: HostTensorType> Tensor<T>
+where
+    T::H: HalideType + Copy,
+{
+    /// Convert the elements to `U` and reshape the result in a single pass
+    ///
+    /// Fails if `shape` doesn't have the same number of elements as the tensor or the tensor is
+    /// not contiguous (see [`Tensor::is_contiguous`])
+    pub fn try_reshape_into<U: HalideType + CastFrom<T::H>>(
+        &self,
+        shape: impl AsTensorShape,
+    ) -> Result<Tensor<Host<U>>> {
+        let dm_type = self.get_dimension_type();
+        ensure!(
+            self.is_contiguous(),
+            ErrorKind::TensorError;
+            "Cannot reshape a tensor with the CaffeC4 dimension type"
+        );
+        let shape = shape.as_tensor_shape();
+        let expected = shape
+            .iter()
+            .map(|&dim| dim.max(0) as usize)
+            .product::<usize>();
+        let input = self.try_host()?;
+        ensure!(
+            input.len() == expected,
+            ErrorKind::SizeMismatch {
+                expected,
+                got: input.len(),
+            };
+            format!("Cannot reshape a tensor of shape {:?} into {shape:?}", self.shape())
+        );
+        let mut output = Tensor::<Host<U>>::new(shape, dm_type);
+        output
+            .try_host_mut()?
+            .iter_mut()
+            .zip(input)
+            .for_each(|(dst, &src)| *dst = U::cast_from(src));
+        Ok(output)
+    }
+
+    /// Convert the elements to `U` keeping the shape, see [`Tensor::try_reshape_into`]
+    pub fn cast<U: HalideType + CastFrom<T::H>>(&self) -> Result<Tensor<Host<U>>> {
+        self.try_reshape_into(self.shape())
+    }
+}
+
+impl<T: HostTensorType> Tensor<T>
+where
+    T::H: HalideType + Copy + PartialOrd,
+{
+    /// The smallest element of the tensor or [`None`] if it's empty
+    ///
+    /// NaNs are ignored unless the first element is NaN
+    pub fn min(&self) -> Option<T::H> {
+        let mut iter = self.host().iter().copied();
+        let first = iter.next()?;
+        Some(iter.fold(first, |min, x| if x < min { x } else { min }))
+    }
+
+    /// The largest element of the tensor or [`None`] if it's empty
+    ///
+    /// NaNs are ignored unless the first element is NaN
+    pub fn max(&self) -> Option<T::H> {
+        let mut iter = self.host().iter().copied();
+        let first = iter.next()?;
+        Some(iter.fold(first, |max, x| if x > max { x } else { max }))
+    }
+
+    /// The flat index and value of the largest element or [`None`] if the tensor is empty
+    ///
+    /// The first index wins ties, NaNs are ignored unless the first element is NaN like
+    /// [`Tensor::max`]
+    pub fn argmax(&self) -> Option<(usize, T::H)> {
+        let mut iter = self.host().iter().copied().enumerate();
+        let first = iter.next()?;
+        Some(iter.fold(first, |max, x| if x.1 > max.1 { x } else { max }))
+    }
+
+    /// The flat indices and values of the `k` largest elements in descending order
+    ///
+    /// Returns all the elements if the tensor has fewer than `k`, ties are ordered by index and
+    /// NaNs are ordered last
+    pub fn topk(&self, k: usize) -> Vec<(usize, T::H)> {
+        let mut elements = self.host().iter().copied().enumerate().collect::<Vec<_>>();
+        // NaN is the only value that isn't comparable to itself
+        let is_nan = |x: &T::H| x.partial_cmp(x).is_none();
+        elements.sort_by(|a, b| match (is_nan(&a.1), is_nan(&b.1)) {
+            (false, false) => b.1.partial_cmp(&a.1).unwrap_or(core::cmp::Ordering::Equal),
+            (a_nan, b_nan) => a_nan.cmp(&b_nan),
+        });
+        elements.truncate(k);
+        elements
+    }
+
+    /// The sum of all the elements of the tensor in the element type, this can overflow for
+    /// small integer types, use [`Tensor::mean`] to accumulate in `f64` instead
+    pub fn sum(&self) -> T::H
+    where
+        T::H: core::iter::Sum,
+    {
+        self.host().iter().copied().sum()
+    }
+
+    /// The mean of all the elements of the tensor accumulated in `f64` or [`None`] if it's empty
+    pub fn mean(&self) -> Option<f64>
+    where
+        f64: CastFrom<T::H>,
+    {
+        let host = self.host();
+        (!host.is_empty())
+            .then(|| host.iter().map(|&x| f64::cast_from(x)).sum::<f64>() / host.len() as f64)
+    }
+}
+
+impl Tensor<Host<f32>> {
+    /// Compute the softmax along `axis` into a new tensor with the same shape
+    ///
+    /// The maximum along the axis is subtracted before exponentiating so large logits don't
+    /// overflow. If every logit along the axis is `-inf` (e.g. fully masked) they are treated as
+    /// equal and get a uniform distribution instead of `NaN`. Fails if the axis is out of range
+    /// or the tensor is not contiguous (see [`Tensor::is_contiguous`])
+    pub fn softmax(&self, axis: usize) -> Result<Self> {
+        ensure!(
+            self.is_contiguous(),
+            ErrorKind::TensorError;
+            "Softmax is not supported on CaffeC4 tensors"
+        );
+        let shape = self.shape();
+        let (outer, len, inner) = split_axis(&shape, axis)?;
+        let input = self.try_host()?;
+        let mut output = Self::new(shape, self.get_dimension_type());
+        let out = output.try_host_mut()?;
+        for o in 0..outer {
+            for i in 0..inner {
+                let index = |k: usize| (o * len + k) * inner + i;
+                let max = (0..len)
+                    .map(|k| input[index(k)])
+                    .fold(f32::NEG_INFINITY, f32::max);
+                if max == f32::NEG_INFINITY {
+                    // -inf - -inf is NaN
+                    (0..len).for_each(|k| out[index(k)] = 1.0 / len as f32);
+                    continue;
+                }
+                let mut sum = 0.0;
+                for k in 0..len {
+                    let exp = (input[index(k)] - max).exp();
+                    out[index(k)] = exp;
+                    sum += exp;
+                }
+                (0..len).for_each(|k| out[index(k)] /= sum);
+            }
+        }
+        Ok(output)
+    }
+}
+
+#[test]
+fn test_tensor_softmax() {
+    let tensor =
+        Tensor::<Host<f32>>::from_iter_with_shape([2, 3], [1.0, 2.0, 3.0, 1.0, 1.0, 1.0]).unwrap();
+    let softmax = tensor.softmax(1).unwrap();
+    let expected = [0.09003057, 0.24472847, 0.66524096];
+    softmax.host()[..3]
+        .iter()
+        .zip(expected)
+        .for_each(|(got, expected)| assert!((got - expected).abs() < 1e-6));
+    softmax.host()[3..]
+        .iter()
+        .for_each(|got| assert!((got - 1.0 / 3.0).abs() < 1e-6));
+
+    let columns = tensor.softmax(0).unwrap();
+    assert!((columns.host()[0] - 0.5).abs() < 1e-6);
+    assert!((columns.host()[0] + columns.host()[3] - 1.0).abs() < 1e-6);
+    assert!(tensor.softmax(2).is_err());
+
+    let masked = Tensor::<Host<f32>>::from_iter_with_shape(
+        [2, 2],
+        [f32::NEG_INFINITY, f32::NEG_INFINITY, 0.0, f32::NEG_INFINITY],
+    )
+    .unwrap();
+    assert_eq!(masked.softmax(1).unwrap().host(), &[0.5, 0.5, 1.0, 0.0]);
+}
+
+#[test]
+fn test_tensor_try_reshape_into() {
+    let tensor = Tensor::<Host<i32>>::from_iter_with_shape([1, 2, 3], 0..6).unwrap();
+    let reshaped = tensor.try_reshape_into::<f32>([1, 6]).unwrap();
+    assert_eq!(reshaped.shape().as_ref(), &[1, 6]);
+    assert_eq!(reshaped.host(), &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert!(tensor.try_reshape_into::<f32>([1, 5]).is_err());
+    assert_eq!(tensor.cast::<u8>().unwrap().host(), &[0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_tensor_reductions() {
+    let tensor = Tensor::<Host<f32>>::from_iter_with_shape([2, 2], [3.0, -1.0, 4.0, 2.0]).unwrap();
+    assert_eq!(tensor.min(), Some(-1.0));
+    assert_eq!(tensor.max(), Some(4.0));
+    assert_eq!(tensor.sum(), 8.0);
+    assert_eq!(tensor.mean(), Some(2.0));
+
+    let bytes = Tensor::<Host<u8>>::from_iter_with_shape([4], [200, 100, 50, 10]).unwrap();
+    assert_eq!(bytes.mean(), Some(90.0));
+    assert_eq!(bytes.max(), Some(200));
+}
+
+#[test]
+fn test_tensor_argmax_topk() {
+    let logits =
+        Tensor::<Host<f32>>::from_iter_with_shape([1, 5], [0.1, 2.5, -1.0, 2.5, 0.7]).unwrap();
+    assert_eq!(logits.argmax(), Some((1, 2.5)));
+    assert_eq!(logits.topk(3), [(1, 2.5), (3, 2.5), (4, 0.7)]);
+    assert_eq!(logits.topk(10).len(), 5);
+    assert!(logits.topk(0).is_empty());
+    let nan = Tensor::<Host<f32>>::from_iter_with_shape([3], [f32::NAN, 1.0, 2.0]).unwrap();
+    assert_eq!(nan.topk(2), [(2, 2.0), (1, 1.0)]);
+
+    let empty = Tensor::<Host<f32>>::new([0], DimensionType::Caffe);
+    assert_eq!(empty.argmax(), None);
+    assert!(empty.topk(3).is_empty());
+}
@@ -30,6 +30,24 @@ impl RawTensor<'_> {
         }
     }
 
+    /// Creates a new host tensor with the same shape and data type as the tensor but laid out
+    /// with the given dimension type
+    ///
+    /// Copying between the two tensors with [`RawTensor::copy_from_host_tensor`] or
+    /// [`RawTensor::copy_to_host_tensor`] converts the layout
+    pub fn create_host_tensor_with_dimension_type(
+        &self,
+        dm_type: super::DimensionType,
+    ) -> RawTensor<'static> {
+        let tensor =
+            unsafe { mnn_sys::Tensor_createFromTensor(self.inner, dm_type.to_mnn_sys(), 1) };
+        assert!(!tensor.is_null());
+        RawTensor {
+            inner: tensor,
+            __marker: PhantomData,
+        }
+    }
+
     /// Copies the data from a host tensor to the self tensor
     pub fn copy_from_host_tensor(&mut self, tensor: &RawTensor) -> Result<()> {
         let ret = unsafe { mnn_sys::Tensor_copyFromHostTensor(self.inner, tensor.inner) };
@@ -72,6 +90,20 @@ impl RawTensor<'_> {
         unsafe { mnn_sys::Tensor_elementSize(self.inner) as usize }
     }
 
+    /// Returns the data type of the elements of the tensor
+    pub fn data_type(&self) -> super::TensorDataType {
+        unsafe { mnn_sys::Tensor_getType(self.inner) }.into()
+    }
+
+    /// Returns the size of the tensor data in bytes computed from the number of elements and the
+    /// data type
+    ///
+    /// This can be smaller than [`RawTensor::size`] for layouts with padding like
+    /// [`DimensionType::NC4HW4`](super::DimensionType::NC4HW4)
+    pub fn size_bytes(&self) -> usize {
+        self.element_size() * self.data_type().byte_size()
+    }
+
     /// Returns the number of dimensions of the tensor
     pub fn dimensions(&self) -> usize {
         unsafe { mnn_sys::Tensor_dimensions(self.inner) as usize }
@@ -98,12 +130,36 @@ impl RawTensor<'_> {
     }
 
     /// Waits for the tensor to be ready
-    pub fn wait(&self, map_type: MapType, finish: bool) {
+    pub fn wait(&self, map_type: impl Into<super::TensorMapType>, finish: bool) {
         unsafe {
-            mnn_sys::Tensor_wait(self.inner, map_type, finish as i32);
+            mnn_sys::Tensor_wait(self.inner, map_type.into().to_mnn_sys(), finish as i32);
         }
     }
 
+    /// Returns the host buffer of the tensor as bytes
+    ///
+    /// Fails if the tensor doesn't have host memory e.g. a tensor allocated on a gpu backend, use
+    /// [`RawTensor::create_host_tensor_from_device`] to get a host copy of it
+    ///
+    /// The slice borrows the tensor so it can't outlive it or be used across a
+    /// [`RawTensor::destroy`]
+    pub fn host_bytes(&self) -> Result<&[u8]> {
+        let data = unsafe { mnn_sys::Tensor_host(self.inner) };
+        crate::ensure!(!data.is_null(), ErrorKind::TensorError; "Tensor has no host memory");
+        Ok(unsafe { core::slice::from_raw_parts(data.cast(), self.size()) })
+    }
+
+    /// Returns the host buffer of the tensor as mutable bytes
+    ///
+    /// Fails if the tensor doesn't have host memory e.g. a tensor allocated on a gpu backend
+    ///
+    /// Like [`RawTensor::host_bytes`] the slice is tied to the lifetime of the borrow of the tensor
+    pub fn host_bytes_mut(&mut self) -> Result<&mut [u8]> {
+        let data = unsafe { mnn_sys::Tensor_host_mut(self.inner) };
+        crate::ensure!(!data.is_null(), ErrorKind::TensorError; "Tensor has no host memory");
+        Ok(unsafe { core::slice::from_raw_parts_mut(data.cast(), self.size()) })
+    }
+
     /// # Safety
     /// This is very unsafe do not use this unless you know what you are doing
     /// Gives a raw pointer to the tensor's data
@@ -131,6 +187,22 @@ impl RawTensor<'_> {
         unsafe { super::Tensor::from_ptr(self.inner) }
     }
 
+    /// Erase the type of an owned tensor, the reverse of [`RawTensor::to_concrete`]
+    ///
+    /// This allows storing tensors of different data types in the same collection. The
+    /// [`RawTensor`] is never dropped so the tensor is leaked unless it's converted back with
+    /// [`RawTensor::to_concrete`] using the same tensor type.
+    pub fn from_concrete<T: super::OwnedTensorType>(tensor: super::Tensor<T>) -> RawTensor<'static>
+    where
+        T::H: HalideType,
+    {
+        let tensor = core::mem::ManuallyDrop::new(tensor);
+        RawTensor {
+            inner: tensor.tensor,
+            __marker: PhantomData,
+        }
+    }
+
     pub(crate) fn from_ptr(inner: *mut mnn_sys::Tensor) -> Self {
         Self {
             inner,
@@ -138,3 +210,51 @@ impl RawTensor<'_> {
         }
     }
 }
+
+#[test]
+fn test_raw_tensor_data_type_and_bytes() {
+    let tensor = crate::Tensor::<crate::Host<i16>>::new([2, 3], crate::DimensionType::NCHW);
+    let raw = RawTensor::from_ptr(tensor.tensor);
+    assert_eq!(raw.data_type(), crate::TensorDataType::I16);
+    assert_eq!(raw.size_bytes(), 12);
+    assert_eq!(raw.host_bytes().unwrap().len(), raw.size());
+}
+
+#[test]
+fn test_raw_tensor_host_bytes_round_trip() {
+    let tensor = crate::Tensor::<crate::Host<f32>>::new([2, 2], crate::DimensionType::NCHW);
+    let mut raw = RawTensor::from_ptr(tensor.tensor);
+    let data: Vec<u8> = [1.0f32, -2.5, 3.25, 0.0]
+        .iter()
+        .flat_map(|f| f.to_ne_bytes())
+        .collect();
+    raw.host_bytes_mut().unwrap().copy_from_slice(&data);
+    assert_eq!(raw.host_bytes().unwrap(), data.as_slice());
+    assert_eq!(tensor.host(), &[1.0, -2.5, 3.25, 0.0]);
+}
+
+#[test]
+fn test_raw_tensor_from_concrete_round_trip() {
+    let floats = crate::Tensor::<crate::Host<f32>>::new_with_data(
+        [2, 2],
+        crate::DimensionType::NCHW,
+        &[1.0, 2.0, 3.0, 4.0],
+    );
+    let bytes = crate::Tensor::<crate::Host<u8>>::new_with_data(
+        [3],
+        crate::DimensionType::NCHW,
+        &[7, 8, 9],
+    );
+    let raws = vec![
+        RawTensor::from_concrete(floats),
+        RawTensor::from_concrete(bytes),
+    ];
+    assert_eq!(raws[0].data_type(), crate::TensorDataType::F32);
+    assert_eq!(raws[1].data_type(), crate::TensorDataType::U8);
+
+    let mut raws = raws.into_iter();
+    let floats = unsafe { raws.next().unwrap().to_concrete::<crate::Host<f32>>() };
+    assert_eq!(floats.host(), &[1.0, 2.0, 3.0, 4.0]);
+    let bytes = unsafe { raws.next().unwrap().to_concrete::<crate::Host<u8>>() };
+    assert_eq!(bytes.host(), &[7, 8, 9]);
+}
@@ -41,3 +41,132 @@ fn compare_cpu_and_coreml_outputs() {
             assert_eq!(cpu.host(), coreml.host());
         });
 }
+
+#[test]
+fn test_schedule_config_validate() {
+    let config = ScheduleConfig::new().with_type(ForwardType::CPU);
+    assert!(config.validate().is_ok());
+    let config = config.with_num_threads(0);
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn test_forward_type_is_gpu() {
+    assert!(ForwardType::CPU.is_cpu());
+    assert!(!ForwardType::CPU.is_gpu());
+    assert!(!ForwardType::Auto.is_cpu() && !ForwardType::Auto.is_gpu());
+    assert!(!ForwardType::All.is_cpu() && !ForwardType::All.is_gpu());
+    #[cfg(feature = "opencl")]
+    assert!(ForwardType::OpenCL.is_gpu());
+}
+
+#[test]
+fn test_schedule_config_configure() {
+    let mut config = ScheduleConfig::new();
+    config.configure(
+        ForwardType::CPU,
+        mnn::PowerMode::High,
+        mnn::PrecisionMode::High,
+        mnn::MemoryMode::Low,
+        2,
+    );
+    assert_eq!(config.get_type(), ForwardType::CPU);
+    assert_eq!(config.get_num_threads(), 2);
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_schedule_config_debug() {
+    let mut config = ScheduleConfig::new();
+    config.configure(
+        ForwardType::CPU,
+        mnn::PowerMode::High,
+        mnn::PrecisionMode::Low,
+        mnn::MemoryMode::Normal,
+        3,
+    );
+    let debug = format!("{config:?}");
+    assert!(debug.contains("type: CPU"));
+    assert!(debug.contains("num_threads: 3"));
+    assert!(debug.contains("power: High"));
+    assert!(debug.contains("precision: Low"));
+    assert!(debug.contains("memory: Normal"));
+}
+
+#[test]
+fn test_schedule_config_memory_limit() -> Result<()> {
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let config = ScheduleConfig::new().with_memory_limit_mb(0);
+    assert_eq!(config.get_memory_limit_mb(), Some(0));
+    let error = net.create_session(config).unwrap_err();
+    assert!(matches!(
+        error.into_inner().current_context(),
+        mnn::ErrorKind::MemoryLimitExceeded
+    ));
+    let config = ScheduleConfig::new().with_memory_limit_mb(u32::MAX);
+    assert!(net.create_session(config).is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_schedule_config_clone_outlives_original() -> Result<()> {
+    let mut config = ScheduleConfig::new();
+    config.configure(
+        ForwardType::CPU,
+        mnn::PowerMode::High,
+        mnn::PrecisionMode::Low,
+        mnn::MemoryMode::Low,
+        2,
+    );
+    let cloned = config.clone();
+    drop(config);
+    assert!(format!("{cloned:?}").contains("precision: Low"));
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let session = net.create_session(cloned)?;
+    net.run_session(&session)?;
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_schedule_config_serde_round_trip() -> Result<()> {
+    let mut config = ScheduleConfig::new();
+    config.configure(
+        ForwardType::CPU,
+        mnn::PowerMode::Low,
+        mnn::PrecisionMode::High,
+        mnn::MemoryMode::Low,
+        2,
+    );
+    let json = serde_json::to_string(&config)?;
+    let restored: ScheduleConfig = serde_json::from_str(&json)?;
+    assert_eq!(format!("{restored:?}"), format!("{config:?}"));
+    assert_eq!(serde_json::to_string(&restored)?, json);
+
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let session = net.create_session(restored)?;
+    net.run_session(&session)?;
+    Ok(())
+}
+
+#[test]
+fn test_backend_config_builder() -> Result<()> {
+    let built = BackendConfig::builder()
+        .power(PowerMode::High)
+        .precision(PrecisionMode::High)
+        .memory(MemoryMode::Low)
+        .build();
+    let shorthand = BackendConfig::new_with(PowerMode::High, PrecisionMode::High, MemoryMode::Low);
+    assert_eq!(format!("{built:?}"), format!("{shorthand:?}"));
+
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let mut run = |config: BackendConfig| -> Result<Vec<f32>> {
+        let session = net.create_session(ScheduleConfig::new().with_backend_config(config))?;
+        let name = net.input_names(&session).remove(0);
+        net.input::<f32>(&session, &name)?.fill(0.5);
+        net.run_session(&session)?;
+        Ok(net.single_output::<f32>(&session)?.try_into_vec()?)
+    };
+    assert_eq!(run(built)?, run(shorthand)?);
+    Ok(())
+}
@@ -35,7 +35,7 @@ fn test_basic_opencl() -> Result<(), Box<dyn std::error::Error>> {
     let outputs = net.outputs(&session);
     outputs.iter().for_each(|x| {
         let tensor = x.tensor::<f32>().expect("No tensor");
-        tensor.wait(ffi::MapType::MAP_TENSOR_READ, true);
+        tensor.wait(TensorMapType::Read, true);
         println!("Waiting for tensor: {}", x.name());
         println!("{}: {:?}", x.name(), tensor.shape());
         // let _ = tensor.create_host_tensor_from_device(true);
@@ -68,3 +68,378 @@ fn test_multi_path_cpu_cpu() {
 // fn test_multi_path_opencl_cpu() {
 //     test_multipath_session(ForwardType::OpenCL, ForwardType::CPU).unwrap();
 // }
+
+#[test]
+fn test_interpreter_try_clone() -> Result<()> {
+    let net = mnn::Interpreter::from_bytes(Model::new())?;
+    let mut cloned = net.try_clone()?;
+    drop(net);
+    let session = cloned.create_session(ScheduleConfig::new())?;
+    assert!(cloned.inputs(&session).size() > 0);
+    Ok(())
+}
+
+#[test]
+fn test_interpreter_try_clone_from_file() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("mnn-rs-clone-{}.mnn", std::process::id()));
+    std::fs::write(&path, Model::new()).unwrap();
+    let mut net = mnn::Interpreter::from_file(&path)?;
+    // The clone uses the model buffer held by MNN instead of reading the file again
+    std::fs::remove_file(&path).unwrap();
+    let mut cloned = net.try_clone()?;
+    let session = cloned.create_session(ScheduleConfig::new())?;
+    cloned.run_session(&session)?;
+    unsafe { net.release_model() };
+    assert!(net.try_clone().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_truncated_model() {
+    let bytes = Model::new();
+    let truncated = &bytes.as_ref()[..bytes.as_ref().len() / 2];
+    let error = mnn::Interpreter::from_bytes(truncated).unwrap_err();
+    assert!(matches!(
+        error.into_inner().current_context(),
+        mnn::ErrorKind::ModelParseFailed
+    ));
+
+    let path = std::env::temp_dir().join(format!("mnn-rs-truncated-{}.mnn", std::process::id()));
+    std::fs::write(&path, truncated).unwrap();
+    let error = mnn::Interpreter::from_file(&path).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    let report = error.into_inner();
+    assert!(matches!(
+        report.current_context(),
+        mnn::ErrorKind::ModelParseFailed
+    ));
+    assert!(format!("{report:?}").contains(&*path.to_string_lossy()));
+}
+
+#[test]
+fn test_set_cache_file_creates_parent_dir() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("mnn-rs-cache-{}", std::process::id()));
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    net.set_cache_file(dir.join("nested").join("model.cache"), 128)?;
+    assert!(dir.join("nested").is_dir());
+    std::fs::remove_dir_all(dir).unwrap();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_set_cache_file_readonly_dir() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = std::env::temp_dir().join(format!("mnn-rs-readonly-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o555)).unwrap();
+    // Permissions are not enforced for privileged users
+    let enforced = std::fs::File::create(dir.join("probe")).is_err();
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let result = net.set_cache_file(dir.join("model.cache"), 128);
+    std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    if enforced {
+        assert!(result.is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_precompile() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("mnn-rs-precompile-{}", std::process::id()));
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    net.precompile(ScheduleConfig::new(), dir.join("model.cache"))?;
+    // The cache can be loaded by another interpreter afterwards
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    net.set_cache_file(dir.join("model.cache"), 128)?;
+    let session = net.create_session(ScheduleConfig::new())?;
+    net.run_session(&session)?;
+    std::fs::remove_dir_all(dir).unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_set_external_file() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("mnn-rs-external-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let weights = dir.join("realesr.mnn.weight");
+    // The test model has no external weights so an empty file is enough
+    std::fs::write(&weights, []).unwrap();
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    assert!(net.set_external_file(dir.join("missing.weight")).is_err());
+    net.set_external_file(&weights)?;
+    let session = net.create_session(ScheduleConfig::new())?;
+    net.run_session(&session)?;
+    std::fs::remove_dir_all(dir).unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_cache_file_size() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("mnn-rs-cache-size-{}", std::process::id()));
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    assert_eq!(net.cache_file_size(), None);
+    net.set_cache_file(dir.join("model.cache"), 128)?;
+    let mut session = net.create_session(ScheduleConfig::new())?;
+    assert!(net.update_cache_file(&mut session)?);
+    assert!(net.cache_file_size().is_some_and(|size| size > 0));
+    std::fs::remove_dir_all(dir).unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_single_output() -> Result<()> {
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let session = net.create_session(ScheduleConfig::new())?;
+    let outputs = net.outputs(&session);
+    let name = outputs.get(0).expect("No outputs").name();
+    let named = net.raw_output(&session, name)?;
+    let single = net.raw_single_output(&session)?;
+    assert_eq!(named.shape(), single.shape());
+    assert!(net.single_output::<f32>(&session).is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_run_with() -> Result<()> {
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let session = net.create_session(ScheduleConfig::new())?;
+    let names = net.input_names(&session);
+    let mut inputs = std::collections::HashMap::new();
+    for name in &names {
+        let shape = net.raw_input(&session, name)?.shape();
+        let mut tensor = Tensor::<Host<f32>>::new(shape, DimensionType::Caffe);
+        tensor.fill(1.0);
+        inputs.insert(name.as_str(), tensor);
+    }
+    let outputs = net.run_with(&session, &inputs)?;
+    assert_eq!(outputs.len(), net.output_names(&session).len());
+    for (name, output) in &outputs {
+        assert_eq!(output.shape(), net.raw_output(&session, name)?.shape());
+    }
+
+    let tensor = inputs.remove(names[0].as_str()).expect("No input");
+    let unknown = std::collections::HashMap::from([("does-not-exist", tensor)]);
+    assert!(net.run_with(&session, &unknown).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_estimate_memory() -> Result<()> {
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    assert!(net.model_buffer_size() > 0);
+    let estimate = net.estimate_memory(&ScheduleConfig::new())?;
+    assert!(estimate > 0.0);
+    // The session used for the estimate is released so the model can still be used
+    let session = net.create_session(ScheduleConfig::new())?;
+    assert!(net.memory(&session)? > 0.0);
+    Ok(())
+}
+
+#[test]
+fn test_session_info() -> Result<()> {
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let session = net.create_session(ScheduleConfig::new())?;
+    let info = net.session_info(&session)?;
+    assert_eq!(info.memory, net.memory(&session)?);
+    assert_eq!(info.flops, net.flops(&session)?);
+    assert!(!info.backends.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_device_tensor_zero() -> Result<()> {
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let session = net.create_session(ScheduleConfig::new())?;
+    let inputs = net.inputs(&session);
+    let input = inputs.get(0).expect("No inputs");
+    let mut tensor = input.tensor::<f32>()?;
+
+    tensor.fill(1.0);
+    let host = tensor.create_host_tensor_from_device(true);
+    assert!(host.host().iter().all(|&v| v == 1.0));
+    tensor.zero();
+    let host = tensor.create_host_tensor_from_device(true);
+    assert!(!host.host().is_empty());
+    assert!(host.host().iter().all(|&v| v == 0.0));
+    Ok(())
+}
+
+#[test]
+fn test_make_input_tensor() -> Result<()> {
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let session = net.create_session(ScheduleConfig::new())?;
+    let name = net
+        .inputs(&session)
+        .get(0)
+        .expect("No inputs")
+        .name()
+        .to_owned();
+    let mut host = net.make_input_tensor::<f32>(&session, &name)?;
+    let mut input = net.input::<f32>(&session, &name)?;
+    assert_eq!(host.shape(), input.shape());
+    host.fill(1.0);
+    input.copy_from_host_tensor(&host)?;
+    assert!(net.make_input_tensor::<u8>(&session, &name).is_err());
+    assert!(net.make_input_tensor::<f32>(&session, "missing").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_run_session_with() -> Result<()> {
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let session = net.create_session(ScheduleConfig::new())?;
+    let input_name = net.input_names(&session)[0].clone();
+    let output_name = net.output_names(&session)[0].clone();
+
+    // The expected output using explicit copies
+    let mut input = net.make_input_tensor::<f32>(&session, &input_name)?;
+    input.fill(0.5);
+    net.input::<f32>(&session, &input_name)?
+        .copy_from_host_tensor(&input)?;
+    net.run_session(&session)?;
+    let expected = net
+        .output::<f32>(&session, &output_name)?
+        .create_host_tensor_from_device(true);
+
+    // Clear the session input so the bound input has to be copied in by the run
+    net.input::<f32>(&session, &input_name)?.fill(0.0);
+    let mut output = expected.clone();
+    output.fill(0.0);
+    let mut bindings = mnn::Bindings::new();
+    bindings
+        .input(&input_name, &input)
+        .output(&output_name, &mut output);
+    net.run_session_with(&session, &mut bindings)?;
+    drop(bindings);
+    assert_eq!(output.host(), expected.host());
+
+    let mut wrong = mnn::Tensor::<mnn::Host<f32>>::new([1, 1], mnn::DimensionType::NCHW);
+    let mut bindings = mnn::Bindings::new();
+    bindings.output(&output_name, &mut wrong);
+    assert!(net.run_session_with(&session, &mut bindings).is_err());
+    let mut bindings = mnn::Bindings::new();
+    bindings.input("missing", &input);
+    assert!(net.run_session_with(&session, &mut bindings).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_model_metadata() -> Result<()> {
+    let net = mnn::Interpreter::from_bytes(Model::new())?;
+    let metadata = net.metadata();
+    assert!(metadata.contains_key("version"));
+    assert!(metadata.values().all(|value| !value.is_empty()));
+    Ok(())
+}
+
+#[test]
+fn test_foreign_session() -> Result<()> {
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let mut other = mnn::Interpreter::from_bytes(Model::new())?;
+    let session = other.create_session(ScheduleConfig::new())?;
+    let name = other.input_names(&session)[0].clone();
+    let output = other.output_names(&session)[0].clone();
+    assert!(net.run_session(&session).is_err());
+    assert!(net.input::<f32>(&session, &name).is_err());
+    assert!(net.raw_input(&session, &name).is_err());
+    assert!(net.output::<f32>(&session, &output).is_err());
+    assert!(net.raw_output(&session, &output).is_err());
+    assert!(net.memory(&session).is_err());
+    assert!(net.flops(&session).is_err());
+    assert!(
+        net.run_session_with_callback(&session, |_, _| true, |_, _| true, true)
+            .is_err()
+    );
+
+    // Sessions of the same interpreter still work
+    let first = net.create_session(ScheduleConfig::new())?;
+    let second = net.create_session(ScheduleConfig::new())?;
+    net.run_session(&first)?;
+    net.run_session(&second)?;
+    other.run_session(&session)?;
+    Ok(())
+}
+
+#[test]
+fn test_io_names() -> Result<()> {
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let session = net.create_session(ScheduleConfig::new())?;
+    let inputs = net.input_names(&session);
+    let outputs = net.output_names(&session);
+    assert!(!inputs.is_empty());
+    assert!(!outputs.is_empty());
+    for name in &inputs {
+        net.raw_input(&session, name)?;
+    }
+    for name in &outputs {
+        net.raw_output(&session, name)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_from_bytes_matches_from_file() -> Result<()> {
+    let run = |mut net: mnn::Interpreter| -> Result<Vec<f32>> {
+        let session = net.create_session(ScheduleConfig::new())?;
+        let name = net.input_names(&session).remove(0);
+        net.input::<f32>(&session, &name)?
+            .fill_with(|i| (i % 255) as f32 / 255.0);
+        net.run_session(&session)?;
+        let output = net.single_output::<f32>(&session)?;
+        Ok(output.create_host_tensor_from_device(true).host().to_vec())
+    };
+    let from_file = run(mnn::Interpreter::from_file(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/assets/realesr.mnn"
+    ))?)?;
+    let from_bytes = run(mnn::Interpreter::from_bytes(Model::new())?)?;
+    assert_eq!(from_file, from_bytes);
+    Ok(())
+}
+
+#[test]
+fn test_device_tensor_try_into_vec() -> Result<()> {
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let session = net.create_session(ScheduleConfig::new())?;
+    let name = net.input_names(&session).remove(0);
+    let mut input = net.input::<f32>(&session, &name)?;
+    input.fill(0.5);
+    let data = input.try_into_vec()?;
+    assert_eq!(data.len(), input.element_size());
+    assert!(data.iter().all(|&v| v == 0.5));
+    Ok(())
+}
+
+#[test]
+fn test_profile_ops() -> Result<()> {
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let session = net.create_session(ScheduleConfig::new())?;
+    let ops = net.profile_ops(&session)?;
+    assert!(!ops.is_empty());
+    assert!(ops.iter().all(|op| !op.op_type.is_empty()));
+    assert!(ops.iter().map(|op| op.flops).sum::<f32>() > 0.0);
+    Ok(())
+}
+
+#[test]
+fn test_set_session_mode() -> Result<()> {
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    net.set_session_mode(SessionMode::Release);
+    net.set_session_mode(SessionMode::MemoryCache);
+    let session = net.create_session(ScheduleConfig::new())?;
+    net.run_session(&session)?;
+    Ok(())
+}
+
+#[test]
+fn test_release_session() -> Result<()> {
+    let mut net = mnn::Interpreter::from_bytes(Model::new())?;
+    let session = net.create_session(ScheduleConfig::new())?;
+    net.run_session(&session)?;
+    net.release_session(session)?;
+    let session = net.create_session(ScheduleConfig::new())?;
+    net.run_session(&session)?;
+    Ok(())
+}
@@ -39,3 +39,22 @@ fn test_multi_threading_opencl() {
 fn test_multi_path_cpu_cpu() {
     test_multipath_session(ForwardType::CPU, ForwardType::CPU).unwrap();
 }
+
+#[cfg(feature = "tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_run_session_async_concurrently() -> Result<()> {
+    let mut first_net = mnn::Interpreter::from_bytes(Model::new())?;
+    let mut second_net = mnn::Interpreter::from_bytes(Model::new())?;
+    let first_session = first_net.create_session(ScheduleConfig::new())?;
+    let second_session = second_net.create_session(ScheduleConfig::new())?;
+    let first = mnn::SharedSession::new(first_net, first_session)?;
+    let second = mnn::SharedSession::new(second_net, second_session)?;
+    // The futures own their handles so they can be spawned
+    let (a, b) = tokio::join!(
+        tokio::spawn(first.run_session_async()),
+        tokio::spawn(second.run_session_async())
+    );
+    a.expect("Failed to join the first run")?;
+    b.expect("Failed to join the second run")?;
+    Ok(())
+}
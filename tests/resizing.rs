@@ -6,7 +6,8 @@ pub fn test_resizing() -> Result<()> {
     let model = std::fs::read("tests/assets/resizing.mnn").expect("No resizing model");
     let mut net = Interpreter::from_bytes(&model).unwrap();
     net.set_cache_file("resizing.cache", 128)?;
-    let config = ScheduleConfig::default();
+    #[allow(unused_mut)]
+    let mut config = ScheduleConfig::default();
     #[cfg(feature = "opencl")]
     config.set_type(ForwardType::OpenCL);
     let mut session = net.create_session(config).unwrap();
@@ -42,3 +43,29 @@ pub fn test_resizing() -> Result<()> {
     println!("{:?}", now.elapsed());
     Ok(())
 }
+
+#[test]
+pub fn test_resizing_raw_inputs() -> Result<()> {
+    let model = std::fs::read("tests/assets/resizing.mnn").expect("No resizing model");
+    let mut net = Interpreter::from_bytes(&model)?;
+    let mut session = net.create_session(ScheduleConfig::default())?;
+    for (name, shape) in [
+        ("mask", &[256, 256][..]),
+        ("original", &[256, 256, 3]),
+        ("inpainted", &[256, 256, 3]),
+    ] {
+        let mut input = net.raw_input(&session, name)?;
+        net.resize_raw_tensor(&mut input, shape);
+    }
+    net.resize_session(&mut session);
+    for name in net.input_names(&session) {
+        assert!(!net.raw_input(&session, &name)?.is_dynamic_unsized());
+    }
+    net.run_session(&session)?;
+    for output in &net.outputs(&session) {
+        let shape = output.raw_tensor().shape();
+        assert!(shape.is_static());
+        assert!(shape.contains(&256), "{} has shape {shape}", output.name());
+    }
+    Ok(())
+}
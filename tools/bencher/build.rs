@@ -0,0 +1,34 @@
+fn git(args: &[&str]) -> Option<String> {
+    std::process::Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|output| output.trim().to_string())
+}
+
+fn main() {
+    // Record the commit the bencher was built from in the metadata of the results
+    let hash = git(&["rev-parse", "--short", "HEAD"]).unwrap_or_else(|| "unknown".into());
+    println!("cargo:rustc-env=BENCHER_GIT_HASH={hash}");
+
+    // HEAD only changes when switching branches, a commit updates the branch ref which is either a
+    // loose file or an entry of packed-refs. A missing loose ref makes cargo rerun this every
+    // build, which is cheap and picks up the ref once it's unpacked.
+    let Some(git_dir) = git(&["rev-parse", "--absolute-git-dir"]) else {
+        return;
+    };
+    let git_dir = std::path::Path::new(&git_dir);
+    println!("cargo:rerun-if-changed={}", git_dir.join("HEAD").display());
+    println!(
+        "cargo:rerun-if-changed={}",
+        git_dir.join("packed-refs").display()
+    );
+    if let Some(head_ref) = git(&["symbolic-ref", "-q", "HEAD"]) {
+        println!(
+            "cargo:rerun-if-changed={}",
+            git_dir.join(head_ref).display()
+        );
+    }
+}
@@ -10,9 +10,60 @@ use std::{
 };
 use thiserror::Error;
 use tracing_subscriber::{layer::SubscriberExt as _, util::SubscriberInitExt as _};
-#[derive(Debug, Clone, Error, Copy)]
-#[error("BenchError: Failed to bench")]
-pub struct BenchError;
+/// The cause of a failed bench, the process exits with [`BenchError::exit_code`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum BenchError {
+    #[error("BenchError: Failed to load the model")]
+    ModelLoad,
+    #[error("BenchError: Failed to create the session")]
+    SessionCreate,
+    #[error("BenchError: Failed to run inference")]
+    Inference,
+    #[error("BenchError: Outputs don't match the expected outputs")]
+    OutputMismatch,
+    #[error("BenchError: IO error")]
+    Io,
+    #[error("BenchError: Failed to parse the config")]
+    ConfigParse,
+    #[error("BenchError: The child process timed out")]
+    Timeout,
+    #[error("BenchError: The results regressed compared to the baseline")]
+    Regression,
+}
+
+impl BenchError {
+    const ALL: [Self; 8] = [
+        Self::ModelLoad,
+        Self::SessionCreate,
+        Self::Inference,
+        Self::OutputMismatch,
+        Self::Io,
+        Self::ConfigParse,
+        Self::Timeout,
+        Self::Regression,
+    ];
+
+    /// The exit code of the process when failing with this error
+    pub fn exit_code(self) -> u8 {
+        match self {
+            Self::ModelLoad => 10,
+            Self::SessionCreate => 11,
+            Self::Inference => 12,
+            Self::OutputMismatch => 13,
+            Self::Io => 14,
+            Self::ConfigParse => 15,
+            Self::Timeout => 16,
+            Self::Regression => 17,
+        }
+    }
+
+    /// The error of a child process in exec mode from its exit code
+    pub fn from_exit_code(code: i32) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|error| i32::from(error.exit_code()) == code)
+    }
+}
 use clap::*;
 
 pub trait ResultExtCC: ResultExt + Sized {
@@ -41,6 +92,10 @@ pub struct Generate {
     // output_types: Vec<TypedOutput>,
     #[arg(short, long)]
     output_type: DataType,
+    /// Dimension type of the generated input files (nchw / nhwc / nc4hw4), defaults to the
+    /// layout of the model's input tensors
+    #[arg(long)]
+    dim_type: Option<mnn::DimensionType>,
 }
 
 #[derive(Debug, Clone, Args)]
@@ -59,9 +114,23 @@ pub struct Cli {
 
 #[derive(Debug, Clone, Subcommand)]
 pub enum Subcommand {
-    Bench(Bench),
+    Bench(Box<Bench>),
     Generate(Generate),
     Completions(Completions),
+    Compare(Compare),
+}
+
+/// Compare two results files of the bench subcommand
+#[derive(Debug, Clone, Parser)]
+pub struct Compare {
+    /// Results of the baseline run, the json output of the bench subcommand or a jsonl file
+    baseline: PathBuf,
+    /// Results of the run compared to the baseline
+    current: PathBuf,
+    /// Exit with an error if the median inference time, memory or flops of any model and config
+    /// grew by more than `PERCENT` compared to the baseline
+    #[arg(long, value_name = "PERCENT", default_value = "5")]
+    threshold: f64,
 }
 #[derive(Debug, Clone, Parser)]
 pub struct Completions {
@@ -75,20 +144,414 @@ pub struct Bench {
     models: Vec<PathBuf>,
     #[command(flatten)]
     sc_items: ScheduleConfigItems,
-    #[arg(short, long, default_value = "10")]
-    warmup: u8,
+    #[command(flatten)]
+    options: BenchOptions,
     #[arg(short, long)]
     output: Option<PathBuf>,
     /// Run in exec mode i.e. run the self binary with the given arguments individually. This
     /// provides a way to bypass segmentation faults in the library.
     #[arg(short, long)]
     exec: bool,
+    /// Print every (model, schedule config) combination that would be benched and exit without
+    /// loading any model
+    #[arg(long)]
+    dry_run: bool,
+    /// Print the exact command line of every child process spawned in exec mode
+    #[arg(long, requires = "exec")]
+    print_commands: bool,
+    /// Kill a child process in exec mode that runs for longer than `SECS` and record a timeout
+    /// error for its combination instead of waiting forever
+    #[arg(long, value_name = "SECS", requires = "exec", value_parser = clap::value_parser!(u64).range(1..))]
+    exec_timeout: Option<u64>,
+    /// Resume an interrupted sweep from a jsonl results file. Every (model, config) pair already
+    /// present in the file is skipped and the missing results are appended to it as they complete.
+    #[arg(long, value_name = "FILE", conflicts_with = "output")]
+    resume: Option<PathBuf>,
+    /// Write the metrics of each model to `DIR/<model file name>.json` (e.g. `yolo.mnn.json`)
+    /// instead of a single output, models with the same file name are rejected
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["output", "resume"])]
+    output_dir: Option<PathBuf>,
+    /// Format of the output, csv writes a row per model and config without the metadata
+    #[arg(long, value_enum, default_value_t = Format::Json, conflicts_with = "resume")]
+    format: Format,
+}
+
+/// How the child processes are spawned in exec mode
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExecOptions {
+    /// Print the command line of every child process
+    pub print_commands: bool,
+    /// Kill a child process that runs for longer than this
+    pub timeout: Option<Duration>,
+}
+
+/// Output format of the bench subcommand
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+
+    /// Render the output of the bench subcommand in this format
+    pub fn render<T: serde::Serialize>(self, output: &Output<T>) -> Result<String> {
+        match self {
+            Self::Json => serde_json::to_string_pretty(output).cc(BenchError::Io),
+            Self::Csv => {
+                let results = serde_json::to_value(&output.results).cc(BenchError::Io)?;
+                Ok(metrics_to_csv(&results))
+            }
+        }
+    }
+}
+
+/// Options that change how a single model / config is benchmarked
+#[derive(Debug, Clone, Args)]
+pub struct BenchOptions {
+    /// Number of warmup iterations or `auto` to warm up until the inference time stabilizes
+    #[arg(short, long, default_value = "10")]
+    warmup: Warmup,
+    /// Number of timed inference runs the statistics are computed from
+    #[arg(long, default_value = "30", value_parser = clap::value_parser!(u32).range(1..))]
+    runs: u32,
+    /// Only measure the time to first inference (model load + session creation + one run),
+    /// skipping the warmup and the cached load comparison
+    #[arg(
+        long,
+        conflicts_with_all = ["persist_cache", "dump_outputs", "include_io_transform", "flamegraph", "profile"]
+    )]
+    cold_start_only: bool,
+    /// Keep the cache files in `DIR` instead of a temporary file
+    ///
+    /// The first run populates the cache and following runs (in other processes) measure the
+    /// load with a warm cache from disk
+    #[arg(long, value_name = "DIR")]
+    persist_cache: Option<PathBuf>,
+    /// Write the outputs of each config (after running the first input sample) to a path
+    /// expanded from the template e.g. `out/{model}_{forward}_{precision}_{output}.bin`
+    ///
+    /// The placeholders are {model}, {forward}, {power}, {precision}, {memory}, {threads} and
+    /// {output} (the name of the output tensor)
+    #[arg(long, value_name = "TEMPLATE")]
+    dump_outputs: Option<OutputTemplate>,
+    /// Pin the bencher (and the library's threads) to a set of cpus e.g. `0-3` or `0,2,4-7`
+    ///
+    /// Only supported on linux, ignored with a warning on other platforms
+    #[arg(long, value_name = "CPUS")]
+    cpu_affinity: Option<CpuSet>,
+    /// Separately time copying the inputs to and the outputs from the session, which includes
+    /// the layout and data type conversion (e.g. the quantization of int8 models) between the
+    /// host and the backend
+    #[arg(long)]
+    include_io_transform: bool,
+    /// Profile the time spent in each op over the timed runs and render it as a flamegraph
+    /// (op type -> op name) to this svg, the collapsed stacks are written next to it with a
+    /// `.folded` extension
+    ///
+    /// Only a single model and config can be benchmarked with this
+    #[arg(long, value_name = "SVG")]
+    flamegraph: Option<PathBuf>,
+    /// Profile the time spent in each op over the timed runs and report the `N` ops with the
+    /// highest mean time
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    profile: Option<u32>,
+}
+
+impl BenchOptions {
+    /// The arguments to pass these options to a child process in exec mode
+    pub fn args(&self) -> Vec<std::ffi::OsString> {
+        let mut args = vec![
+            "--warmup".into(),
+            self.warmup.to_string().into(),
+            "--runs".into(),
+            self.runs.to_string().into(),
+        ];
+        if self.cold_start_only {
+            args.push("--cold-start-only".into());
+        }
+        if let Some(persist_cache) = &self.persist_cache {
+            args.push("--persist-cache".into());
+            args.push(persist_cache.into());
+        }
+        if let Some(dump_outputs) = &self.dump_outputs {
+            args.push("--dump-outputs".into());
+            args.push(dump_outputs.to_string().into());
+        }
+        if let Some(cpu_affinity) = self.cpu_affinity {
+            args.push("--cpu-affinity".into());
+            args.push(cpu_affinity.to_string().into());
+        }
+        if self.include_io_transform {
+            args.push("--include-io-transform".into());
+        }
+        if let Some(flamegraph) = &self.flamegraph {
+            args.push("--flamegraph".into());
+            args.push(flamegraph.into());
+        }
+        if let Some(profile) = self.profile {
+            args.push("--profile".into());
+            args.push(profile.to_string().into());
+        }
+        args
+    }
+
+    /// The cpu affinity actually applied to the process, [`None`] on unsupported platforms
+    pub fn applied_cpu_affinity(&self) -> Option<CpuSet> {
+        self.cpu_affinity.filter(|_| CpuSet::SUPPORTED)
+    }
+}
+
+/// A set of cpus to pin the bencher to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuSet([u64; CpuSet::WORDS]);
+
+impl CpuSet {
+    /// Same as `CPU_SETSIZE` on linux
+    pub const MAX_CPUS: usize = 1024;
+    const WORDS: usize = Self::MAX_CPUS / 64;
+    pub const SUPPORTED: bool = cfg!(target_os = "linux");
+
+    pub fn contains(&self, cpu: usize) -> bool {
+        cpu < Self::MAX_CPUS && self.0[cpu / 64] & (1 << (cpu % 64)) != 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..Self::MAX_CPUS).filter(|&cpu| self.contains(cpu))
+    }
+
+    /// Set the affinity of the current thread, threads spawned afterwards (and child processes)
+    /// inherit it
+    #[cfg(target_os = "linux")]
+    pub fn apply(&self) -> Result<()> {
+        let mut set: libc::cpu_set_t = unsafe { core::mem::zeroed() };
+        self.iter()
+            .for_each(|cpu| unsafe { libc::CPU_SET(cpu, &mut set) });
+        let ret =
+            unsafe { libc::sched_setaffinity(0, core::mem::size_of::<libc::cpu_set_t>(), &set) };
+        if ret != 0 {
+            return Err(Report::new(BenchError::Io).attach_printable(format!(
+                "Failed to set the cpu affinity to {self}: {}",
+                std::io::Error::last_os_error()
+            )));
+        }
+        tracing::info!("Pinned to cpus {self}");
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn apply(&self) -> Result<()> {
+        tracing::warn!("--cpu-affinity is not supported on this platform, ignoring it");
+        Ok(())
+    }
+}
+
+impl core::str::FromStr for CpuSet {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |cpu: &str| -> Result<usize, String> {
+            let cpu = cpu
+                .trim()
+                .parse::<usize>()
+                .map_err(|e| format!("Invalid cpu {cpu:?} in {s:?}: {e}"))?;
+            if cpu >= Self::MAX_CPUS {
+                return Err(format!(
+                    "Cpu {cpu} is out of range, expected less than {}",
+                    Self::MAX_CPUS
+                ));
+            }
+            Ok(cpu)
+        };
+        let mut set = Self([0; Self::WORDS]);
+        for range in s.split(',') {
+            let (start, end) = match range.split_once('-') {
+                Some((start, end)) => (parse(start)?, parse(end)?),
+                None => (parse(range)?, parse(range)?),
+            };
+            if start > end {
+                return Err(format!(
+                    "Invalid cpu range {range:?}, start is after the end"
+                ));
+            }
+            (start..=end).for_each(|cpu| set.0[cpu / 64] |= 1 << (cpu % 64));
+        }
+        Ok(set)
+    }
+}
+
+/// Formats as a list of ranges, e.g. `0-3,6`
+impl core::fmt::Display for CpuSet {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut cpus = self.iter().peekable();
+        let mut first = true;
+        while let Some(start) = cpus.next() {
+            let mut end = start;
+            while cpus.next_if_eq(&(end + 1)).is_some() {
+                end += 1;
+            }
+            if !first {
+                f.write_str(",")?;
+            }
+            first = false;
+            if start == end {
+                write!(f, "{start}")?;
+            } else {
+                write!(f, "{start}-{end}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl serde::Serialize for CpuSet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// How many warmup iterations to run before timing the inference
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Warmup {
+    Fixed(u8),
+    /// Run until the coefficient of variation of the last [`Warmup::WINDOW`] iterations drops
+    /// below [`Warmup::THRESHOLD`] or [`Warmup::MAX_ITERATIONS`] is reached
+    Auto,
+}
+
+impl Warmup {
+    pub const WINDOW: usize = 5;
+    pub const THRESHOLD: f64 = 0.05;
+    pub const MAX_ITERATIONS: usize = 100;
+
+    /// Run `f` as warmup and return the number of iterations used
+    pub fn run(self, mut f: impl FnMut(usize) -> Result<()>) -> Result<usize> {
+        match self {
+            Self::Fixed(n) => {
+                for c in 0..n as usize {
+                    f(c)?;
+                }
+                Ok(n as usize)
+            }
+            Self::Auto => {
+                let mut times = Vec::with_capacity(Self::MAX_ITERATIONS);
+                while times.len() < Self::MAX_ITERATIONS {
+                    let (_, time) = timeit(|| f(times.len()))?;
+                    times.push(time.as_secs_f64());
+                    if times.len() >= Self::WINDOW
+                        && coefficient_of_variation(&times[times.len() - Self::WINDOW..])
+                            < Self::THRESHOLD
+                    {
+                        break;
+                    }
+                }
+                Ok(times.len())
+            }
+        }
+    }
+}
+
+impl core::str::FromStr for Warmup {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            s => s
+                .parse()
+                .map(Self::Fixed)
+                .map_err(|e| format!("Invalid warmup {s}, expected a number or `auto`: {e}")),
+        }
+    }
+}
+
+impl core::fmt::Display for Warmup {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Fixed(n) => write!(f, "{n}"),
+            Self::Auto => f.write_str("auto"),
+        }
+    }
+}
+
+/// The standard deviation divided by the mean
+pub fn coefficient_of_variation(samples: &[f64]) -> f64 {
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+    if mean == 0.0 {
+        0.0
+    } else {
+        variance.sqrt() / mean
+    }
+}
+
+/// Statistics of the timed inference runs
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InferenceStats {
+    pub min: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+    pub stddev: Duration,
+}
+
+impl InferenceStats {
+    /// Compute the statistics of the samples, the percentiles use the nearest rank
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let percentile = |p: f64| {
+            let rank = (p * sorted.len() as f64).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1]
+        };
+        let secs = samples.iter().map(Duration::as_secs_f64);
+        let mean = secs.clone().sum::<f64>() / samples.len() as f64;
+        let variance = secs.map(|s| (s - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+        Self {
+            min: sorted[0],
+            p50: percentile(0.5),
+            p95: percentile(0.95),
+            max: sorted[sorted.len() - 1],
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
 }
 
 #[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Config {
-    inputs: BTreeMap<String, PathBuf>,
+    inputs: BTreeMap<String, InputFiles>,
     outputs: BTreeMap<String, ConfigData>,
+    /// Dimension type of the input files, the layout of the model's input tensors if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dim_type: Option<mnn::DimensionType>,
+}
+
+/// The input file(s) of a tensor
+///
+/// With a list of files one inference is run per sample to measure the throughput, inputs with
+/// a single file are reused for every sample.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum InputFiles {
+    Single(PathBuf),
+    Multiple(Vec<PathBuf>),
+}
+
+impl InputFiles {
+    pub fn paths(&self) -> &[PathBuf] {
+        match self {
+            Self::Single(path) => core::slice::from_ref(path),
+            Self::Multiple(paths) => paths,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -161,10 +624,32 @@ impl Config {
     pub fn find(model: impl AsRef<Path>) -> Result<Self> {
         let model = model.as_ref();
         let config = model.with_extension("json");
-        let config = std::fs::read(config).cc(BenchError)?;
-        let config: Config = serde_json::from_slice(&config).cc(BenchError)?;
+        let config = std::fs::read(config).cc(BenchError::Io)?;
+        let config: Config = serde_json::from_slice(&config).cc(BenchError::ConfigParse)?;
         Ok(config)
     }
+
+    /// The number of input samples, every input must either have a single file or the same
+    /// number of files as the others
+    pub fn samples(&self) -> Result<usize> {
+        let samples = self
+            .inputs
+            .values()
+            .map(|files| files.paths().len())
+            .max()
+            .unwrap_or(1);
+        for (name, files) in &self.inputs {
+            let count = files.paths().len();
+            if count != 1 && count != samples {
+                return Err(
+                    Report::new(BenchError::ConfigParse).attach_printable(format!(
+                        "Input {name} has {count} files but other inputs have {samples}"
+                    )),
+                );
+            }
+        }
+        Ok(samples.max(1))
+    }
 }
 
 #[derive(Debug, Clone, Args)]
@@ -181,6 +666,12 @@ pub struct ScheduleConfigItems {
     /// Comma separated list of memory modes (low / high / normal)
     #[arg(short, long,value_delimiter = ',', num_args= 1.., default_value = "normal")]
     memory: Vec<mnn::MemoryMode>,
+    /// Comma separated list of thread counts for the cpu backend
+    ///
+    /// Only swept on the cpu backend, on the gpu backends MNN reads the thread count as a
+    /// tuning mode so they keep MNN's default
+    #[arg(long, value_delimiter = ',', num_args = 1.., default_value = "4", value_parser = clap::value_parser!(i32).range(1..))]
+    threads: Vec<i32>,
 }
 
 pub struct ScheduleConfigItem {
@@ -188,6 +679,7 @@ pub struct ScheduleConfigItem {
     pub power: mnn::PowerMode,
     pub precision: mnn::PrecisionMode,
     pub memory: mnn::MemoryMode,
+    pub threads: i32,
 }
 
 impl ScheduleConfigItem {
@@ -196,22 +688,37 @@ impl ScheduleConfigItem {
         power: mnn::PowerMode,
         precision: mnn::PrecisionMode,
         memory: mnn::MemoryMode,
+        threads: i32,
     ) -> Self {
         Self {
             forward,
             power,
             precision,
             memory,
+            threads,
         }
     }
 
+    /// A normalized key for this item used to match against previously recorded results
+    pub fn key(&self) -> [String; 5] {
+        [
+            normalize_key(self.forward.to_str()),
+            normalize_key(self.power.to_str()),
+            normalize_key(self.precision.to_str()),
+            normalize_key(self.memory.to_str()),
+            self.threads.to_string(),
+        ]
+    }
+
     pub fn into_schedule_config(self) -> ScheduleConfig {
         let mut sc = mnn::ScheduleConfig::new();
-        let mut bc = mnn::BackendConfig::new();
-        bc.set_power_mode(self.power);
-        bc.set_precision_mode(self.precision);
-        bc.set_memory_mode(self.memory);
-        sc.set_type(self.forward).set_backend_config(bc);
+        sc.configure(
+            self.forward,
+            self.power,
+            self.precision,
+            self.memory,
+            self.threads,
+        );
         sc
     }
 }
@@ -222,6 +729,7 @@ impl ScheduleConfigItems {
             || self.power.is_empty()
             || self.precision.is_empty()
             || self.memory.is_empty()
+            || self.threads.is_empty()
     }
 
     pub fn is_single(&self) -> bool {
@@ -229,7 +737,42 @@ impl ScheduleConfigItems {
     }
 
     pub fn combinations(&self) -> usize {
-        self.forward.len() * self.power.len() * self.precision.len() * self.memory.len()
+        self.forward
+            .iter()
+            .map(|forward| self.threads_for(*forward).len())
+            .sum::<usize>()
+            * self.power.len()
+            * self.precision.len()
+            * self.memory.len()
+    }
+
+    /// The thread counts to sweep for a forward type, the gpu backends read the thread count
+    /// as a tuning mode so only the cpu backend gets the requested counts
+    fn threads_for(&self, forward: mnn::ForwardType) -> Vec<i32> {
+        if forward == mnn::ForwardType::CPU {
+            self.threads.clone()
+        } else {
+            vec![mnn::ScheduleConfig::new().get_num_threads()]
+        }
+    }
+
+    /// Drop the forward types whose backend isn't available on this device
+    pub fn retain_available(&mut self) -> Result<()> {
+        self.forward.retain(|forward| {
+            let available = forward.is_available();
+            if !available {
+                tracing::warn!(
+                    "Skipping {}, the backend is not available",
+                    forward.to_str()
+                );
+            }
+            available
+        });
+        if self.forward.is_empty() {
+            return Err(Report::new(BenchError::ConfigParse)
+                .attach_printable("None of the requested backends are available"));
+        }
+        Ok(())
     }
 }
 
@@ -244,25 +787,130 @@ impl IntoIterator for ScheduleConfigItems {
             .map(|f| {
                 self.power.iter().map(|p| {
                     self.precision.iter().map(|pr| {
-                        self.memory
-                            .iter()
-                            .map(|m| ScheduleConfigItem::new(*f, *p, *pr, *m))
+                        self.memory.iter().map(|m| {
+                            self.threads_for(*f)
+                                .into_iter()
+                                .map(|t| ScheduleConfigItem::new(*f, *p, *pr, *m, t))
+                        })
                     })
                 })
             })
             .flatten()
             .flatten()
             .flatten()
+            .flatten()
             .collect();
         outputs.into_iter()
     }
 }
 type Result<T, E = Report<BenchError>> = core::result::Result<T, E>;
 
+/// Information about the machine and the build the results were measured with
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Metadata {
+    pub hostname: Option<String>,
+    pub cpu: Option<String>,
+    pub logical_cores: Option<usize>,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub mnn_version: &'static str,
+    pub bencher_version: &'static str,
+    pub bencher_git_hash: &'static str,
+    pub bencher_profile: &'static str,
+}
+
+impl Metadata {
+    pub fn collect() -> Self {
+        Self {
+            hostname: hostname(),
+            cpu: cpu_model(),
+            logical_cores: std::thread::available_parallelism().ok().map(usize::from),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            mnn_version: mnn::version(),
+            bencher_version: env!("CARGO_PKG_VERSION"),
+            bencher_git_hash: env!("BENCHER_GIT_HASH"),
+            bencher_profile: if cfg!(debug_assertions) {
+                "debug"
+            } else {
+                "release"
+            },
+        }
+    }
+}
+
+fn hostname() -> Option<String> {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .or_else(|| command_output("hostname", &[]))
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+}
+
+fn cpu_model() -> Option<String> {
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok();
+    cpuinfo
+        .as_deref()
+        .and_then(|cpuinfo| {
+            cpuinfo.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                matches!(key.trim(), "model name" | "Hardware" | "Model").then(|| value.trim())
+            })
+        })
+        .map(str::to_string)
+        .or_else(|| command_output("sysctl", &["-n", "machdep.cpu.brand_string"]))
+        .filter(|cpu| !cpu.is_empty())
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    std::process::Command::new(program)
+        .args(args)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|output| output.trim().to_string())
+}
+
+/// The output of the bench subcommand
 #[derive(Debug, serde::Serialize)]
+pub struct Output<T> {
+    pub metadata: Metadata,
+    pub results: T,
+}
+
+/// The metrics of a model, the results of every run (in process, in exec mode and the lines of a
+/// `--resume` file) are made of these
+///
+/// The metrics are kept serialized since the results of child processes in exec mode are only
+/// available as json.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct Metrics {
     pub model: PathBuf,
-    pub metrics: Vec<Metric>,
+    /// The metadata embedded in the model header e.g. bizCode, uuid and converter version
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub model_metadata: BTreeMap<String, String>,
+    pub metrics: Vec<serde_json::Value>,
+}
+
+impl Metrics {
+    /// Merge consecutive results of the same model into one entry
+    pub fn merge(results: Vec<Result<Self>>) -> Vec<Result<Self>> {
+        results
+            .into_iter()
+            .fold(Vec::new(), |mut merged: Vec<Result<Self>>, result| {
+                match (merged.last_mut(), result) {
+                    (Some(Ok(last)), Ok(metrics)) if last.model == metrics.model => {
+                        last.metrics.extend(metrics.metrics)
+                    }
+                    (_, result) => merged.push(result),
+                }
+                merged
+            })
+    }
 }
 
 #[derive(Debug)]
@@ -271,36 +919,118 @@ pub struct Metric {
     pub flops: f32,                  // in Mflops
     pub initial_load_time: Duration, // in ms
     pub cached_load_time: Duration,  // in ms
-    pub inference_time: Duration,    // in ms
+    pub inference_time: Duration,    // in ms, the mean of the timed runs
+    pub inference: InferenceStats,
+    pub throughput: f64, // in inferences per second over the input samples
+    pub warmup_iterations: usize,
+    pub cold_start_time: Option<Duration>, // in ms, only set with --cold-start-only
+    /// Whether the cached load used a cache from a previous run, only set with --persist-cache
+    pub warm_cache: Option<bool>,
+    /// Size of the cache file used for the cached load in bytes, `None` if no cache was written
+    pub cache_size: Option<u64>,
+    pub cpu_affinity: Option<CpuSet>,
+    /// Mean time to copy the inputs to the session, only set with --include-io-transform
+    pub input_transform: Option<Duration>,
+    /// Mean time to copy the outputs to the host, only set with --include-io-transform
+    pub output_transform: Option<Duration>,
+    /// The ops with the highest mean time, only set with --profile
+    pub hottest_ops: Option<Vec<OpTime>>,
     pub schedule_config: ScheduleConfig,
     pub outputs: BTreeMap<String, f64>, // mean absolute error
 }
 
+/// The mean time and flops of an op over the profiled runs
+#[derive(Debug, Clone)]
+pub struct OpTime {
+    pub name: String,
+    pub op_type: String,
+    pub flops: f32,     // in Mflops
+    pub time: Duration, // in ms, the mean over the runs
+}
+
+impl serde::Serialize for OpTime {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct as _;
+        let mut state = serializer.serialize_struct("OpTime", 4)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("type", &self.op_type)?;
+        state.serialize_field("flops", &format!("{:.2}M", self.flops))?;
+        state.serialize_field("time", &format!("{:.3}ms", self.time.as_secs_f64() * 1e3))?;
+        state.end()
+    }
+}
+
 impl serde::Serialize for Metric {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         use serde::ser::SerializeStruct as _;
-        let mut state = serializer.serialize_struct("Metric", 6)?;
+        let mut state = serializer.serialize_struct("Metric", 9)?;
         state.serialize_field("memory", &format!("{:.0}MiB", self.memory))?;
         state.serialize_field("flops", &format!("{:.0}M", self.flops))?;
         state.serialize_field(
             "initial_load_time",
             &format!("{}ms", self.initial_load_time.as_millis()),
         )?;
+        if let Some(cpu_affinity) = self.cpu_affinity {
+            state.serialize_field("cpu_affinity", &cpu_affinity)?;
+        }
+        if let Some(cold_start_time) = self.cold_start_time {
+            // The steady state metrics are not measured in cold start mode
+            state.serialize_field(
+                "cold_start_time",
+                &format!("{}ms", cold_start_time.as_millis()),
+            )?;
+            state.serialize_field("schedule_config", &self.schedule_config)?;
+            state.serialize_field("outputs", &self.outputs)?;
+            return state.end();
+        }
         state.serialize_field(
             "cached_load_time",
             &format!("{}ms", self.cached_load_time.as_millis()),
         )?;
+        if let Some(warm_cache) = self.warm_cache {
+            state.serialize_field("warm_cache", &warm_cache)?;
+        }
+        if let Some(cache_size) = self.cache_size {
+            state.serialize_field("cache_size", &cache_size)?;
+        }
         state.serialize_field(
             "inference_time",
             &format!("{}ms", self.inference_time.as_millis()),
         )?;
+        let ms = |d: Duration| format!("{:.3}ms", d.as_secs_f64() * 1e3);
+        state.serialize_field("inference_min", &ms(self.inference.min))?;
+        state.serialize_field("inference_p50", &ms(self.inference.p50))?;
+        state.serialize_field("inference_p95", &ms(self.inference.p95))?;
+        state.serialize_field("inference_max", &ms(self.inference.max))?;
+        state.serialize_field("inference_stddev", &ms(self.inference.stddev))?;
+        state.serialize_field("throughput", &format!("{:.2}/s", self.throughput))?;
+        state.serialize_field("warmup_iterations", &self.warmup_iterations)?;
+        if let Some(input_transform) = self.input_transform {
+            state.serialize_field("input_transform", &ms(input_transform))?;
+        }
+        if let Some(output_transform) = self.output_transform {
+            state.serialize_field("output_transform", &ms(output_transform))?;
+        }
+        if let Some(hottest_ops) = &self.hottest_ops {
+            state.serialize_field("hottest_ops", hottest_ops)?;
+        }
         state.serialize_field("schedule_config", &self.schedule_config)?;
         state.serialize_field("outputs", &self.outputs)?;
         state.end()
     }
 }
 
-pub fn main() -> Result<()> {
+pub fn main() -> std::process::ExitCode {
+    match run() {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(report) => {
+            eprintln!("{report:?}");
+            std::process::ExitCode::from(report.current_context().exit_code())
+        }
+    }
+}
+
+pub fn run() -> Result<()> {
     let cli = Cli::parse();
     // let cli = Bench::parse();
     // let indicatif_layer = IndicatifLayer::new();
@@ -310,8 +1040,9 @@ pub fn main() -> Result<()> {
         .init();
 
     match cli.subcommand {
-        Subcommand::Bench(cli) => bench_main(cli)?,
+        Subcommand::Bench(cli) => bench_main(*cli)?,
         Subcommand::Generate(cli) => generate_main(cli)?,
+        Subcommand::Compare(cli) => compare_main(cli)?,
         Subcommand::Completions(cli) => {
             use clap_complete::aot::generate;
 
@@ -330,17 +1061,17 @@ pub fn generate_main(cli: Generate) -> Result<()> {
         let mut cfg = Config {
             inputs: Default::default(),
             outputs: Default::default(),
+            dim_type: cli.dim_type,
         };
-        let mut net = mnn::Interpreter::from_file(&model).cc(BenchError)?;
+        let mut net = mnn::Interpreter::from_file(&model).cc(BenchError::ModelLoad)?;
         let sc = ScheduleConfig::new()
             .with_type(cli.forward)
-            .with_backend_config(
-                mnn::BackendConfig::new()
-                    .with_power_mode(cli.power)
-                    .with_precision_mode(cli.precision)
-                    .with_memory_mode(cli.memory),
-            );
-        let session = net.create_session(sc).cc(BenchError)?;
+            .with_backend_config(mnn::BackendConfig::new_with(
+                cli.power,
+                cli.precision,
+                cli.memory,
+            ));
+        let session = net.create_session(sc).cc(BenchError::SessionCreate)?;
         let inputs = net.inputs(&session);
         for input in &inputs {
             let model_name = model
@@ -350,20 +1081,25 @@ pub fn generate_main(cli: Generate) -> Result<()> {
             let name = format!("{}_input_{}.bin", model_name, input.name());
             let path = model.with_file_name(name);
             let mut tensor = input.raw_tensor();
-            unsafe {
-                let mut host = tensor.create_host_tensor_from_device(false);
-                host.unchecked_host_bytes().fill(1);
-                tensor.copy_from_host_tensor(&host).cc(BenchError)?;
-                std::fs::write(&path, host.unchecked_host_bytes()).cc(BenchError)?;
-            }
+            let mut host = create_host_tensor(&tensor, cfg.dim_type);
+            // Not zeroed since many models map all zero inputs to a constant output which
+            // wouldn't catch much when checking the outputs
+            host.host_bytes_mut().cc(BenchError::Inference)?.fill(1);
+            tensor
+                .copy_from_host_tensor(&host)
+                .cc(BenchError::Inference)?;
+            std::fs::write(&path, host.host_bytes().cc(BenchError::Io)?).cc(BenchError::Io)?;
+            host.destroy();
             cfg.inputs.insert(
                 input.name().to_string(),
-                dunce::canonicalize(path).cc(BenchError)?,
+                InputFiles::Single(dunce::canonicalize(path).cc(BenchError::Io)?),
             );
         }
         drop(inputs);
 
-        net.run_session(&session).cc(BenchError)?;
+        net.run_session(&session).cc(BenchError::Inference)?;
+        // The outputs of gpu backends are only ready after waiting for the session
+        net.wait(&session);
 
         let outputs = net.outputs(&session);
         for output in &outputs {
@@ -373,100 +1109,662 @@ pub fn generate_main(cli: Generate) -> Result<()> {
                 .to_string_lossy();
             let name = format!("{}_output_{}.bin", model_name, output.name());
             let path = model.with_file_name(name);
-            let tensor = output.raw_tensor();
-            unsafe {
-                std::fs::write(
-                    &path,
-                    tensor
-                        .create_host_tensor_from_device(true)
-                        .unchecked_host_bytes(),
-                )
-                .cc(BenchError)?;
-            }
+            let host = output.raw_tensor().create_host_tensor_from_device(true);
+            std::fs::write(&path, host.host_bytes().cc(BenchError::Io)?).cc(BenchError::Io)?;
+            host.destroy();
             cfg.outputs.insert(
                 output.name().to_string(),
                 ConfigData {
                     data_type: cli.output_type,
-                    path: dunce::canonicalize(path).cc(BenchError)?,
+                    path: dunce::canonicalize(path).cc(BenchError::Io)?,
                 },
             );
         }
         std::fs::write(
             model.with_extension("json"),
-            serde_json::to_string_pretty(&cfg).cc(BenchError)?,
+            serde_json::to_string_pretty(&cfg).cc(BenchError::Io)?,
         )
-        .cc(BenchError)?;
+        .cc(BenchError::Io)?;
     }
     Ok(())
 }
 
-pub fn bench_main(cli: Bench) -> Result<()> {
+pub fn bench_main(mut cli: Bench) -> Result<()> {
+    if cli.dry_run {
+        return dry_run(&cli.models, cli.sc_items);
+    }
+    cli.sc_items.retain_available()?;
+    if let Some(cpu_affinity) = cli.options.cpu_affinity {
+        cpu_affinity.apply()?;
+    }
+    if cli.options.flamegraph.is_some() && (cli.models.len() != 1 || !cli.sc_items.is_single()) {
+        return Err(Report::new(BenchError::ConfigParse)
+            .attach_printable("--flamegraph needs a single model and config"));
+    }
     let multi_progress = indicatif::MultiProgress::new();
-    let output = if !cli.exec {
-        let results = bench_all(cli.models.iter(), cli.sc_items, cli.warmup, &multi_progress);
-        serde_json::to_string_pretty(&results).cc(BenchError)?
-    } else {
-        let results = exec_bench_all(cli.models.iter(), cli.sc_items, cli.warmup, &multi_progress)?;
-        serde_json::to_string_pretty(&results).cc(BenchError)?
+    let exec = cli.exec.then_some(ExecOptions {
+        print_commands: cli.print_commands,
+        timeout: cli.exec_timeout.map(Duration::from_secs),
+    });
+    if let Some(resume) = cli.resume {
+        return resume_bench_all(
+            &resume,
+            cli.models.iter(),
+            cli.sc_items,
+            &cli.options,
+            exec,
+            &multi_progress,
+        );
+    }
+    let run = |models: &[PathBuf]| -> Result<String> {
+        let mut results = Vec::new();
+        bench_each(
+            models.iter(),
+            cli.sc_items.clone(),
+            &cli.options,
+            exec,
+            &multi_progress,
+            |_, _| false,
+            |result| {
+                results.push(result);
+                Ok(())
+            },
+        )?;
+        cli.format.render(&Output {
+            metadata: Metadata::collect(),
+            results: Metrics::merge(results),
+        })
     };
     use std::io::Write;
+    if let Some(dir) = &cli.output_dir {
+        std::fs::create_dir_all(dir)
+            .cc(BenchError::Io)
+            .attach_printable_lazy(|| format!("Failed to create {}", dir.display()))?;
+        let outputs = output_dir_paths(dir, &cli.models, cli.format)?;
+        for (model, out_f) in cli.models.iter().zip(outputs) {
+            let output = run(core::slice::from_ref(model))?;
+            std::fs::write(&out_f, output.as_bytes())
+                .cc(BenchError::Io)
+                .attach_printable_lazy(|| format!("Failed to write {}", out_f.display()))?;
+        }
+        return Ok(());
+    }
+    let output = run(&cli.models)?;
     if let Some(out_f) = cli.output {
         std::fs::File::create(out_f)
-            .cc(BenchError)?
+            .cc(BenchError::Io)?
             .write_all(output.as_bytes())
-            .cc(BenchError)?;
+            .cc(BenchError::Io)?;
     } else {
-        Term::stdout().write_all(output.as_bytes()).cc(BenchError)?;
+        Term::stdout()
+            .write_all(output.as_bytes())
+            .cc(BenchError::Io)?;
     }
     Ok(())
 }
 
-pub fn exec_bench_all<'a>(
-    models: impl Iterator<Item = &'a PathBuf>,
-    sc_items: ScheduleConfigItems,
-    warmup: u8,
-    mp: &MultiProgress,
-) -> Result<Vec<Result<serde_json::Value>>> {
-    let self_exe = std::env::current_exe().cc(BenchError)?;
-    let result: Vec<Result<serde_json::Value>> = models
-        .map(|m| {
-            let pb = indicatif::ProgressBar::new(sc_items.combinations() as u64)
-                .with_prefix(format!("{}", m.file_name().unwrap().to_string_lossy()))
-                .with_style(
-                    indicatif::ProgressStyle::default_bar()
-                        .template("{prefix} {bar:80} {pos}/{len} {msg}")
-                        .expect("Failed to build progress bar style"),
+/// The `--output-dir` file of each model, the extension of the format is appended to the file
+/// name of the model (e.g. `yolo.v5.mnn.json`) so models that only differ in their extension
+/// don't overwrite each other
+///
+/// Fails if two models map to the same file e.g. models with the same name in different
+/// directories
+pub fn output_dir_paths(dir: &Path, models: &[PathBuf], format: Format) -> Result<Vec<PathBuf>> {
+    let mut seen = BTreeMap::new();
+    models
+        .iter()
+        .map(|model| {
+            let name = model
+                .file_name()
+                .ok_or_else(|| Report::new(BenchError::Io))
+                .attach_printable_lazy(|| format!("Invalid model path {}", model.display()))?;
+            let mut name = name.to_os_string();
+            name.push(".");
+            name.push(format.extension());
+            let path = dir.join(name);
+            if let Some(other) = seen.insert(path.clone(), model) {
+                return Err(
+                    Report::new(BenchError::ConfigParse).attach_printable(format!(
+                        "{} and {} would both be written to {}",
+                        other.display(),
+                        model.display(),
+                        path.display()
+                    )),
                 );
-            mp.insert(0, pb.clone());
-            sc_items
-                .clone()
-                .into_iter()
-                .map({
-                    |sc| {
-                        pb.set_message(format!(
-                            "{:?}:power->{:?}:precision->{:?}:memory->{:?}",
-                            sc.forward, sc.power, sc.precision, sc.memory
-                        ));
-                        let out = exec_bench(&self_exe, warmup, sc, m, &mp);
-                        pb.inc(1);
-                        out
+            }
+            Ok(path)
+        })
+        .collect()
+}
+
+/// The columns of the csv output, the timings are in ms
+pub const CSV_COLUMNS: [&str; 21] = [
+    "model",
+    "forward",
+    "power",
+    "precision",
+    "memory_mode",
+    "num_threads",
+    "memory_mib",
+    "flops_m",
+    "initial_load_time_ms",
+    "cached_load_time_ms",
+    "cold_start_time_ms",
+    "inference_time_ms",
+    "inference_min_ms",
+    "inference_p50_ms",
+    "inference_p95_ms",
+    "inference_max_ms",
+    "inference_stddev_ms",
+    "throughput_per_s",
+    "warmup_iterations",
+    "input_transform_ms",
+    "output_transform_ms",
+];
+
+/// Flatten the serialized results of the bench subcommand into csv with a row per model and
+/// config
+///
+/// Works on the serialized results so it handles the results of child processes in exec mode the
+/// same way, failed results are skipped.
+pub fn metrics_to_csv(results: &serde_json::Value) -> String {
+    use serde_json::Value;
+    /// The value without its unit e.g. `12ms` -> `12`
+    fn number(value: Option<&Value>) -> String {
+        match value {
+            Some(Value::String(s)) => s.trim_end_matches(|c: char| !c.is_ascii_digit()).to_owned(),
+            Some(Value::Number(n)) => n.to_string(),
+            _ => String::new(),
+        }
+    }
+    fn escape(field: &str) -> String {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_owned()
+        }
+    }
+
+    let rows = collect_metrics(results);
+    let mut csv = CSV_COLUMNS.join(",");
+    csv.push('\n');
+    for (model, metric) in rows {
+        let text = |pointer: &str| {
+            metric
+                .pointer(pointer)
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_owned()
+        };
+        let mut row = vec![
+            model.to_owned(),
+            text("/schedule_config/type"),
+            text("/schedule_config/backend_config/power"),
+            text("/schedule_config/backend_config/precision"),
+            text("/schedule_config/backend_config/memory"),
+            number(metric.pointer("/schedule_config/num_threads")),
+        ];
+        row.extend(
+            [
+                "memory",
+                "flops",
+                "initial_load_time",
+                "cached_load_time",
+                "cold_start_time",
+                "inference_time",
+                "inference_min",
+                "inference_p50",
+                "inference_p95",
+                "inference_max",
+                "inference_stddev",
+                "throughput",
+                "warmup_iterations",
+                "input_transform",
+                "output_transform",
+            ]
+            .map(|field| number(metric.get(field))),
+        );
+        let row = row.iter().map(|field| escape(field)).collect::<Vec<_>>();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Find the (model, metric) pairs in the serialized results of the bench subcommand
+///
+/// Handles the output with or without the metadata, the results of child processes in exec mode
+/// and the lines of a jsonl results file, failed results are skipped.
+pub fn collect_metrics(results: &serde_json::Value) -> Vec<(&str, &serde_json::Value)> {
+    use serde_json::Value;
+    fn collect<'v>(value: &'v Value, rows: &mut Vec<(&'v str, &'v Value)>) {
+        match value {
+            Value::Array(items) => items.iter().for_each(|item| collect(item, rows)),
+            Value::Object(object) => {
+                let model = object.get("model").and_then(Value::as_str);
+                if let Some(ok) = object.get("Ok") {
+                    collect(ok, rows);
+                } else if let Some(err) = object.get("Err") {
+                    tracing::warn!("Skipping a failed result: {err}");
+                } else if let Some(results) = object.get("results") {
+                    collect(results, rows);
+                } else if let (Some(model), Some(Value::Array(metrics))) =
+                    (model, object.get("metrics"))
+                {
+                    rows.extend(metrics.iter().map(|metric| (model, metric)));
+                } else if let (Some(model), Some(metric)) = (model, object.get("metric")) {
+                    rows.push((model, metric));
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut rows = Vec::new();
+    collect(results, &mut rows);
+    rows
+}
+
+/// The metrics compared by the compare subcommand, an increase of any of them is a regression
+///
+/// The median inference time is compared instead of the mean `inference_time` since it has sub
+/// millisecond resolution and is robust against outliers. The io transform times are only
+/// compared if both runs used `--include-io-transform`.
+pub const COMPARED_METRICS: [&str; 5] = [
+    "inference_p50",
+    "memory",
+    "flops",
+    "input_transform",
+    "output_transform",
+];
+
+/// The change of the [`COMPARED_METRICS`] of a model and config between two runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub model: PathBuf,
+    /// The normalized (forward, power, precision, memory, threads) of the schedule config
+    pub config: [String; 5],
+    /// The (baseline, current) value of each of the [`COMPARED_METRICS`], `None` if it's missing
+    /// in either run (e.g. cold start results have no inference time) or the baseline is zero
+    /// since no relative change can be computed from it
+    pub values: [Option<(f64, f64)>; 5],
+}
+
+impl Comparison {
+    /// The relative change of each of the [`COMPARED_METRICS`] in percent
+    pub fn deltas(&self) -> [Option<f64>; 5] {
+        self.values.map(|value| {
+            value.map(|(baseline, current)| {
+                if baseline == current {
+                    0.0
+                } else {
+                    (current - baseline) / baseline.abs() * 100.0
+                }
+            })
+        })
+    }
+
+    /// The metrics that grew by more than `threshold` percent
+    pub fn regressions(&self, threshold: f64) -> Vec<&'static str> {
+        COMPARED_METRICS
+            .into_iter()
+            .zip(self.deltas())
+            .filter(|(_, delta)| delta.is_some_and(|delta| delta > threshold))
+            .map(|(metric, _)| metric)
+            .collect()
+    }
+}
+
+/// Match the metrics of two runs by model and schedule config
+///
+/// Metrics that are only present in one of the runs are skipped with a warning.
+pub fn compare_results(
+    baseline: &serde_json::Value,
+    current: &serde_json::Value,
+) -> Vec<Comparison> {
+    /// The value without its unit e.g. `12ms` -> `12`
+    fn number(metric: &serde_json::Value, field: &str) -> Option<f64> {
+        match metric.get(field)? {
+            serde_json::Value::String(s) => s
+                .trim_end_matches(|c: char| !c.is_ascii_digit())
+                .parse()
+                .ok(),
+            value => value.as_f64(),
+        }
+    }
+    let mut baseline = collect_metrics(baseline)
+        .into_iter()
+        .filter_map(|(model, metric)| Some((result_key(model, metric)?, metric)))
+        .collect::<BTreeMap<_, _>>();
+    let comparisons = collect_metrics(current)
+        .into_iter()
+        .filter_map(|(model, metric)| {
+            let key = result_key(model, metric)?;
+            let Some(base) = baseline.remove(&key) else {
+                tracing::warn!("No baseline for {} {:?}", key.0.display(), key.1);
+                return None;
+            };
+            Some(Comparison {
+                values: COMPARED_METRICS.map(|field| {
+                    Some((
+                        number(base, field).filter(|&base| base != 0.0)?,
+                        number(metric, field)?,
+                    ))
+                }),
+                model: key.0,
+                config: key.1,
+            })
+        })
+        .collect();
+    baseline.into_keys().for_each(|(model, config)| {
+        tracing::warn!("No current result for {} {config:?}", model.display())
+    });
+    comparisons
+}
+
+/// Render the comparisons as a tab separated table, regressions are marked with a `!`
+pub fn render_comparisons(comparisons: &[Comparison], threshold: f64) -> String {
+    let mut table = ["model", "config"]
+        .into_iter()
+        .chain(COMPARED_METRICS)
+        .collect::<Vec<_>>()
+        .join("\t");
+    table.push('\n');
+    for comparison in comparisons {
+        let mut row = vec![
+            comparison.model.display().to_string(),
+            comparison.config.join("/"),
+        ];
+        row.extend(
+            comparison
+                .values
+                .iter()
+                .zip(comparison.deltas())
+                .map(|(value, delta)| match (value, delta) {
+                    (Some((baseline, current)), Some(delta)) => {
+                        let flag = if delta > threshold { " !" } else { "" };
+                        format!("{baseline} -> {current} ({delta:+.1}%){flag}")
                     }
+                    _ => "-".to_owned(),
+                }),
+        );
+        table.push_str(&row.join("\t"));
+        table.push('\n');
+    }
+    table
+}
+
+/// Read a results file, either a single json document or a jsonl file with a result per line
+pub fn read_results(path: impl AsRef<Path>) -> Result<serde_json::Value> {
+    let path = path.as_ref();
+    let data = std::fs::read_to_string(path)
+        .cc(BenchError::Io)
+        .attach_printable_lazy(|| format!("Failed to read {}", path.display()))?;
+    if let Ok(value) = serde_json::from_str(&data) {
+        return Ok(value);
+    }
+    data.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str)
+        .collect::<Result<Vec<serde_json::Value>, _>>()
+        .map(serde_json::Value::Array)
+        .cc(BenchError::ConfigParse)
+        .attach_printable_lazy(|| format!("Failed to parse {}", path.display()))
+}
+
+pub fn compare_main(cli: Compare) -> Result<()> {
+    use std::io::Write;
+    let baseline = read_results(&cli.baseline)?;
+    let current = read_results(&cli.current)?;
+    let comparisons = compare_results(&baseline, &current);
+    if comparisons.is_empty() {
+        return Err(
+            Report::new(BenchError::ConfigParse).attach_printable(format!(
+                "No matching results in {} and {}",
+                cli.baseline.display(),
+                cli.current.display()
+            )),
+        );
+    }
+    Term::stdout()
+        .write_all(render_comparisons(&comparisons, cli.threshold).as_bytes())
+        .cc(BenchError::Io)?;
+    let regressions = comparisons
+        .iter()
+        .flat_map(|comparison| {
+            comparison
+                .regressions(cli.threshold)
+                .into_iter()
+                .map(move |metric| {
+                    format!(
+                        "{} of {} {} regressed",
+                        metric,
+                        comparison.model.display(),
+                        comparison.config.join("/")
+                    )
                 })
+        })
+        .collect::<Vec<_>>();
+    if regressions.is_empty() {
+        return Ok(());
+    }
+    Err(regressions.into_iter().fold(
+        Report::new(BenchError::Regression).attach_printable(format!(
+            "Metrics grew by more than {}% compared to the baseline",
+            cli.threshold
+        )),
+        |report, regression| report.attach_printable(regression),
+    ))
+}
+
+/// Lowercase and strip any separators so that `LowBf16`, `low_bf16` and `low-bf16` all match
+pub fn normalize_key(s: &str) -> String {
+    s.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Get the (model, config) key of a serialized metric of a model from its schedule config
+pub fn result_key(
+    model: impl AsRef<Path>,
+    metric: &serde_json::Value,
+) -> Option<(PathBuf, [String; 5])> {
+    let model = model.as_ref();
+    let model = dunce::canonicalize(model).unwrap_or_else(|_| model.to_path_buf());
+    let sc = metric.get("schedule_config")?;
+    let field = |pointer: &str| {
+        sc.pointer(pointer)
+            .and_then(|v| v.as_str())
+            .map(normalize_key)
+            .unwrap_or_else(|| "normal".into())
+    };
+    Some((
+        model,
+        [
+            sc.get("type")?.as_str().map(normalize_key)?,
+            field("/backend_config/power"),
+            field("/backend_config/precision"),
+            field("/backend_config/memory"),
+            sc.get("num_threads")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(4)
+                .to_string(),
+        ],
+    ))
+}
+
+/// Read the keys of all the completed results in a jsonl file
+/// Lines that fail to parse (for example a line truncated by an interrupted run) are ignored
+pub fn completed_results(
+    path: impl AsRef<Path>,
+) -> Result<std::collections::BTreeSet<(PathBuf, [String; 5])>> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(Default::default());
+    }
+    let data = std::fs::read_to_string(path)
+        .cc(BenchError::Io)
+        .attach_printable_lazy(|| format!("Failed to read {}", path.display()))?;
+    Ok(data
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(
+            |line| match serde_json::from_str::<serde_json::Value>(line) {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    tracing::warn!("Skipping malformed line in {}: {e}", path.display());
+                    None
+                }
+            },
+        )
+        .flat_map(|value| {
+            collect_metrics(&value)
+                .into_iter()
+                .filter_map(|(model, metric)| result_key(model, metric))
                 .collect::<Vec<_>>()
         })
-        .flatten()
-        .collect();
-    Ok(result)
+        .collect())
+}
+
+/// Bench the combinations missing from a jsonl results file and append a [`Metrics`] line for
+/// each of them as soon as it completes
+pub fn resume_bench_all<'a>(
+    results: &Path,
+    models: impl Iterator<Item = &'a PathBuf>,
+    sc_items: ScheduleConfigItems,
+    options: &BenchOptions,
+    exec: Option<ExecOptions>,
+    mp: &MultiProgress,
+) -> Result<()> {
+    use std::io::Write;
+    let done = completed_results(results)?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(results)
+        .cc(BenchError::Io)
+        .attach_printable_lazy(|| format!("Failed to open {}", results.display()))?;
+    let (mut skipped, mut failed) = (0usize, 0usize);
+    bench_each(
+        models,
+        sc_items,
+        options,
+        exec,
+        mp,
+        |model, sc| {
+            let skip = done.contains(&(model.to_path_buf(), sc.key()));
+            skipped += skip as usize;
+            skip
+        },
+        |result| {
+            match result {
+                Ok(metrics) => {
+                    let line = serde_json::to_string(&metrics).cc(BenchError::Io)?;
+                    writeln!(file, "{line}").cc(BenchError::Io)?;
+                    file.flush().cc(BenchError::Io)?;
+                }
+                Err(e) => {
+                    failed += 1;
+                    tracing::error!("{e:?}");
+                }
+            }
+            Ok(())
+        },
+    )?;
+    eprintln!(
+        "Skipped {skipped} already completed results, {failed} failed, results appended to {}",
+        results.display()
+    );
+    Ok(())
+}
+
+/// Bench every combination of the models and configs, in this process or in a child process per
+/// combination in exec mode, and hand each result to `sink` as soon as it completes
+///
+/// `skip` is called with the canonical model path and the config before each combination, the
+/// combinations it returns true for are not benched.
+pub fn bench_each<'a>(
+    models: impl Iterator<Item = &'a PathBuf>,
+    sc_items: ScheduleConfigItems,
+    options: &BenchOptions,
+    exec: Option<ExecOptions>,
+    mp: &MultiProgress,
+    mut skip: impl FnMut(&Path, &ScheduleConfigItem) -> bool,
+    mut sink: impl FnMut(Result<Metrics>) -> Result<()>,
+) -> Result<()> {
+    let self_exe = std::env::current_exe().cc(BenchError::Io)?;
+    for m in models {
+        let pb = indicatif::ProgressBar::new(sc_items.combinations() as u64)
+            .with_prefix(format!(
+                "{}",
+                m.file_name().unwrap_or_default().to_string_lossy()
+            ))
+            .with_style(if sc_items.is_single() {
+                indicatif::ProgressStyle::default_bar()
+                    .template("{prefix} {msg}")
+                    .expect("Failed to build progress bar style")
+            } else {
+                indicatif::ProgressStyle::default_bar()
+                    .template("{prefix} {bar:80} {pos}/{len} {msg}")
+                    .expect("Failed to build progress bar style")
+            });
+        mp.add(pb.clone());
+        let model = match dunce::canonicalize(m)
+            .cc(BenchError::ModelLoad)
+            .attach_printable_lazy(|| format!("Failed to find {}", m.display()))
+        {
+            Ok(model) => model,
+            Err(e) => {
+                sink(Err(e))?;
+                continue;
+            }
+        };
+        // The child processes read the metadata themselves in exec mode
+        let mut model_metadata = None;
+        for sc in sc_items.clone() {
+            pb.inc(1);
+            if skip(&model, &sc) {
+                continue;
+            }
+            pb.set_message(format!(
+                "{:?}:power->{:?}:precision->{:?}:memory->{:?}:threads->{}",
+                sc.forward, sc.power, sc.precision, sc.memory, sc.threads
+            ));
+            let result = if let Some(exec) = exec {
+                exec_bench(&self_exe, options, sc, m, exec, mp)
+            } else {
+                let metadata = match &model_metadata {
+                    Some(metadata) => Ok(metadata),
+                    None => mnn::Interpreter::from_file(&model)
+                        .cc(BenchError::ModelLoad)
+                        .map(|net| &*model_metadata.insert(net.metadata())),
+                };
+                metadata.and_then(|metadata| {
+                    let metric = bench(options, sc.into_schedule_config(), m, mp)?;
+                    Ok(Metrics {
+                        model: model.clone(),
+                        model_metadata: metadata.clone(),
+                        metrics: vec![serde_json::to_value(metric).cc(BenchError::Io)?],
+                    })
+                })
+            };
+            sink(result.attach_printable_lazy(|| format!("Failed to bench {}", m.display())))?;
+        }
+        pb.finish();
+    }
+    Ok(())
 }
 
 pub fn exec_bench(
     exec: &Path,
-    w: u8,
+    options: &BenchOptions,
     sc: ScheduleConfigItem,
     model: impl AsRef<Path>,
+    exec_options: ExecOptions,
     mp: &MultiProgress,
-) -> Result<serde_json::Value> {
-    let mut child = std::process::Command::new(exec)
+) -> Result<Metrics> {
+    let mut command = std::process::Command::new(exec);
+    command
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped())
         .arg("bench")
@@ -479,82 +1777,222 @@ pub fn exec_bench(
         .arg(sc.precision.to_str())
         .arg("--forward")
         .arg(sc.forward.to_str())
-        .arg("--warmup")
-        .arg(w.to_string())
-        .spawn()
-        .cc(BenchError)?;
+        .arg("--threads")
+        .arg(sc.threads.to_string())
+        .args(options.args());
+    if exec_options.print_commands {
+        mp.println(command_line(&command)).cc(BenchError::Io)?;
+    }
+    let mut child = command.spawn().cc(BenchError::Io)?;
     let child_stderr = child.stderr.take().expect("Failed to get stderr");
     let child_stdout = child.stdout.take().expect("Failed to get stdout");
     let progress = p_read(child_stderr);
     progress.enable_steady_tick(Duration::from_millis(200));
     mp.insert(0, progress.clone());
-    let output = child.wait().cc(BenchError)?;
+    // Read the output while waiting so a child filling the pipe doesn't look like it's hung
+    let stdout = std::thread::spawn(move || {
+        serde_json::from_reader::<_, serde_json::Value>(child_stdout).cc(BenchError::Io)
+    });
+    let Some(output) = wait_timeout(&mut child, exec_options.timeout)? else {
+        progress.finish_and_clear();
+        return Err(Report::new(BenchError::Timeout).attach_printable(format!(
+            "Killed {exec} after {}s",
+            exec_options.timeout.unwrap_or_default().as_secs(),
+            exec = exec.display()
+        )));
+    };
     if !output.success() {
-        return Err(Report::new(BenchError)
+        // Segfaults and other crashes in the library are reported as inference failures
+        let error = output
+            .code()
+            .and_then(BenchError::from_exit_code)
+            .unwrap_or(BenchError::Inference);
+        return Err(Report::new(error)
             .attach_printable(format!("Failed to execute {exec}", exec = exec.display())));
     }
     progress.finish_and_clear();
-    let metrics = serde_json::from_reader(child_stdout).cc(BenchError)?;
-    Ok(metrics)
+    let output = stdout
+        .join()
+        .expect("Failed to read the output of the child")?;
+    // The child benches a single model and config, its metadata is only recorded by the parent
+    let result = match output.get("results").and_then(serde_json::Value::as_array) {
+        Some(results) if results.len() == 1 => &results[0],
+        _ => {
+            return Err(Report::new(BenchError::Io)
+                .attach_printable(format!("Unexpected output from {}", exec.display())));
+        }
+    };
+    if let Some(err) = result.get("Err") {
+        return Err(Report::new(BenchError::Inference)
+            .attach_printable(format!("The child process failed: {err}")));
+    }
+    result
+        .get("Ok")
+        .cloned()
+        .map(serde_json::from_value)
+        .transpose()
+        .cc(BenchError::Io)?
+        .ok_or_else(|| Report::new(BenchError::Io))
+        .attach_printable_lazy(|| format!("Unexpected output from {}", exec.display()))
 }
 
-pub fn bench_all(
-    models: impl Iterator<Item = impl AsRef<Path>>,
-    sc_items: ScheduleConfigItems,
-    warmup: u8,
-    multi_progress: &MultiProgress,
-) -> Vec<Result<Metrics>> {
-    let result: Vec<Result<Metrics>> = models
-        .map(|m| -> Result<Metrics> {
-            // Check create_session_time without cache
-            let pb = indicatif::ProgressBar::new(sc_items.combinations() as u64)
-                .with_prefix(format!(
-                    "{}",
-                    m.as_ref().file_name().unwrap().to_string_lossy()
-                ))
-                .with_style(if sc_items.is_single() {
-                    indicatif::ProgressStyle::default_bar()
-                        .template("{prefix} {msg}")
-                        .expect("Failed to build progress bar style")
-                } else {
-                    indicatif::ProgressStyle::default_bar()
-                        .template("{prefix} {bar:80} {pos}/{len} {msg}")
-                        .expect("Failed to build progress bar style")
-                });
-
-            multi_progress.add(pb.clone());
-            let metrics = sc_items
-                .clone()
-                .into_iter()
-                .map(|sc| {
-                    pb.set_message(format!(
-                        "{:?}:power->{:?}:precision->{:?}:memory->{:?}",
-                        sc.forward, sc.power, sc.precision, sc.memory
-                    ));
-                    let o = bench(
-                        warmup,
-                        sc.into_schedule_config(),
-                        m.as_ref(),
-                        &multi_progress,
-                    )
-                    .cc(BenchError);
-                    pb.inc(1);
-                    o
-                })
-                .collect::<Result<Vec<Metric>>>()
-                .cc(BenchError)?;
-            Ok(Metrics {
-                model: dunce::canonicalize(m).cc(BenchError)?,
-                metrics,
+/// Wait for a child process to exit, killing it if it runs for longer than `timeout`
+///
+/// Returns [`None`] if the child was killed
+pub fn wait_timeout(
+    child: &mut std::process::Child,
+    timeout: Option<Duration>,
+) -> Result<Option<std::process::ExitStatus>> {
+    let Some(timeout) = timeout else {
+        return child.wait().cc(BenchError::Io).map(Some);
+    };
+    let start = std::time::Instant::now();
+    loop {
+        if let Some(status) = child.try_wait().cc(BenchError::Io)? {
+            return Ok(Some(status));
+        }
+        if start.elapsed() >= timeout {
+            child.kill().cc(BenchError::Io)?;
+            child.wait().cc(BenchError::Io)?;
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Format a command as a line that can be pasted into a shell
+pub fn command_line(command: &std::process::Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| {
+            let arg = arg.to_string_lossy();
+            if !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./=:,".contains(c))
+            {
+                arg.into_owned()
+            } else {
+                format!("'{}'", arg.replace('\'', r"'\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Print every (model, schedule config) combination as a line of `<model>\t<config json>`
+pub fn dry_run(models: &[PathBuf], sc_items: ScheduleConfigItems) -> Result<()> {
+    use std::io::Write;
+    let mut stdout = std::io::stdout().lock();
+    for model in models {
+        for sc in sc_items.clone() {
+            let sc = serde_json::to_string(&sc.into_schedule_config()).cc(BenchError::Io)?;
+            writeln!(stdout, "{}\t{sc}", model.display()).cc(BenchError::Io)?;
+        }
+    }
+    eprintln!(
+        "{} combinations of {} models and {} configs",
+        models.len() * sc_items.combinations(),
+        models.len(),
+        sc_items.combinations()
+    );
+    Ok(())
+}
+
+/// The input data of a model loaded from its [`Config`]
+pub struct Inputs {
+    data: Vec<(String, Vec<Vec<u8>>)>,
+    dim_type: Option<mnn::DimensionType>,
+    samples: usize,
+}
+
+impl Inputs {
+    pub fn load(config: &Config) -> Result<Self> {
+        let samples = config.samples()?;
+        let data = config
+            .inputs
+            .iter()
+            .map(|(name, files)| {
+                let data = files
+                    .paths()
+                    .iter()
+                    .map(|path| std::fs::read(path).cc(BenchError::Io))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok((name.clone(), data))
             })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            data,
+            dim_type: config.dim_type,
+            samples,
         })
-        .collect();
-    result
+    }
+
+    /// Copy the data of `sample` to the input tensors of the session
+    pub fn set(&self, net: &mnn::Interpreter, session: &mnn::Session, sample: usize) -> Result<()> {
+        for (name, data) in &self.data {
+            let input = &data[sample % data.len()];
+            let mut tensor = net.raw_input(session, name).cc(BenchError::Inference)?;
+            let mut host = create_host_tensor(&tensor, self.dim_type);
+            let bytes = host.host_bytes_mut().cc(BenchError::Inference)?;
+            if bytes.len() != input.len() {
+                let expected = bytes.len();
+                host.destroy();
+                return Err(
+                    Report::new(BenchError::ConfigParse).attach_printable(format!(
+                        "Input {name} has {} bytes but the tensor needs {expected}",
+                        input.len()
+                    )),
+                );
+            }
+            bytes.copy_from_slice(input);
+            tensor
+                .copy_from_host_tensor(&host)
+                .cc(BenchError::Inference)?;
+            host.destroy();
+        }
+        Ok(())
+    }
+}
+
+/// Compare the outputs of the session with the expected outputs of the config and return the
+/// mean absolute error of each output
+pub fn compare_outputs(
+    net: &mnn::Interpreter,
+    session: &mnn::Session,
+    config: &Config,
+    bar: &indicatif::ProgressBar,
+) -> Result<BTreeMap<String, f64>> {
+    let not_terminal = !std::io::stdout().is_terminal();
+    let mut outputs = BTreeMap::new();
+    for (name, cd) in config.outputs.iter() {
+        bar.set_message(format!("Checking output {name}"));
+        not_terminal.then(|| eprintln!("Checking output {name}"));
+        let host = net
+            .raw_output(session, name)
+            .cc(BenchError::Inference)?
+            .create_host_tensor_from_device(true);
+        let output = host.host_bytes().cc(BenchError::Inference)?.to_vec();
+        host.destroy();
+        let expected = std::fs::read(&cd.path).cc(BenchError::Io)?;
+        if output.len() != expected.len() {
+            return Err(
+                Report::new(BenchError::OutputMismatch).attach_printable(format!(
+                    "Output {name} has {} bytes but the expected output has {}",
+                    output.len(),
+                    expected.len()
+                )),
+            );
+        }
+        let mas = cd.data_type.mas(&output, &expected);
+        outputs.insert(name.clone(), mas);
+    }
+    Ok(outputs)
 }
 
 // #[tracing::instrument(skip(model))]
 pub fn bench(
-    w: u8,
+    options: &BenchOptions,
     sc: ScheduleConfig,
     model: impl AsRef<Path>,
     mp: &MultiProgress,
@@ -565,7 +2003,14 @@ pub fn bench(
     let not_terminal = !std::io::stdout().is_terminal();
 
     tracing::info!("Benching {:?}", sc);
-    let mut net = mnn::Interpreter::from_file(&model).cc(BenchError)?;
+    sc.validate().cc(BenchError::ConfigParse)?;
+    if options.cold_start_only {
+        return cold_start_bench(sc, model, &bar).map(|metric| Metric {
+            cpu_affinity: options.applied_cpu_affinity(),
+            ..metric
+        });
+    }
+    let mut net = mnn::Interpreter::from_file(&model).cc(BenchError::ModelLoad)?;
 
     bar.set_message("Creating session without cache");
     not_terminal.then(|| eprintln!("Creating session without cache"));
@@ -573,84 +2018,122 @@ pub fn bench(
         tracing::trace!("Creating session without cache");
         net.create_session(sc.clone())
     })
-    .cc(BenchError)?;
-    let temp_file = temp_file_path()?;
-    net.set_cache_file(&temp_file, 128).cc(BenchError)?;
-    net.update_cache_file(&mut uncached).cc(BenchError)?;
+    .cc(BenchError::SessionCreate)?;
+    let (cache_file, temp_file, warm_cache) = match &options.persist_cache {
+        Some(dir) => {
+            let path = persisted_cache_path(dir, &model, &sc)?;
+            let warm = path.metadata().is_ok_and(|m| m.len() > 0);
+            (path, None, Some(warm))
+        }
+        None => {
+            let temp_file = temp_file_path()?;
+            (temp_file.to_path_buf(), Some(temp_file), None)
+        }
+    };
+    if warm_cache == Some(true) {
+        tracing::info!("Using the cache from a previous run {cache_file:?}");
+    } else {
+        net.set_cache_file(&cache_file, 128)
+            .cc(BenchError::SessionCreate)?;
+        net.update_cache_file(&mut uncached)
+            .cc(BenchError::SessionCreate)?;
+    }
     drop(uncached);
     drop(net);
-    let mut net = mnn::Interpreter::from_file(&model).cc(BenchError)?;
-    net.set_cache_file(&temp_file, 128).cc(BenchError)?;
+    let mut net = mnn::Interpreter::from_file(&model).cc(BenchError::ModelLoad)?;
+    net.set_cache_file(&cache_file, 128)
+        .cc(BenchError::SessionCreate)?;
     bar.set_message("Creating session with cache");
     not_terminal.then(|| eprintln!("Creating session with cache"));
     let (session, cached_load_time) = timeit(|| {
-        tracing::trace!("Creating session with cache {temp_file:?}");
+        tracing::trace!("Creating session with cache {cache_file:?}");
         net.create_session(sc.clone())
     })
-    .cc(BenchError)?;
-    for c in 0..w {
+    .cc(BenchError::SessionCreate)?;
+    let warmup_iterations = options.warmup.run(|c| {
         bar.set_message(format!("Warming up {c}"));
         not_terminal.then(|| eprintln!("Warming up {c}"));
-        net.run_session(&session).cc(BenchError)?;
-    }
-    let (_, inference_time) = timeit(|| -> Result<()> {
-        for c in 0..5 {
-            bar.set_message(format!("Running inference {c}"));
-            not_terminal.then(|| eprintln!("Running inference {c}"));
-            net.run_session(&session).cc(BenchError)?;
-            net.wait(&session);
-        }
+        net.run_session(&session).cc(BenchError::Inference)?;
+        net.wait(&session);
         Ok(())
     })?;
-    let inference_time = inference_time / 5;
-    let config = Config::find(&model).cc(BenchError).unwrap_or_default();
-    for (name, path) in config.inputs.iter() {
-        let input = std::fs::read(path).cc(BenchError)?;
-        bar.set_message(format!("Setting input {name}"));
-        not_terminal.then(|| eprintln!("Setting input {name}"));
-        unsafe {
-            let mut tensor = net.raw_input(&session, name).cc(BenchError)?;
-            let mut host = tensor.create_host_tensor_from_device(false);
-            host.unchecked_host_bytes().copy_from_slice(&input);
-            tensor.copy_from_host_tensor(&host).cc(BenchError)?;
-            drop(host);
-        }
+    tracing::info!("Warmed up with {warmup_iterations} iterations");
+    let mut samples = Vec::with_capacity(options.runs as usize);
+    for c in 0..options.runs {
+        bar.set_message(format!("Running inference {c}"));
+        not_terminal.then(|| eprintln!("Running inference {c}"));
+        let (_, time) = timeit(|| -> Result<()> {
+            net.run_session(&session).cc(BenchError::Inference)?;
+            net.wait(&session);
+            Ok(())
+        })?;
+        samples.push(time);
     }
+    let inference_time = samples.iter().sum::<Duration>() / options.runs;
+    let inference = InferenceStats::from_samples(&samples);
+    if let Some(path) = &options.flamegraph {
+        bar.set_message("Profiling ops");
+        not_terminal.then(|| eprintln!("Profiling ops"));
+        write_flamegraph(&mut net, &session, path, options.runs as usize)?;
+    }
+    let hottest_ops = match options.profile {
+        Some(top) => {
+            bar.set_message("Profiling ops");
+            not_terminal.then(|| eprintln!("Profiling ops"));
+            Some(hottest_ops(
+                &mut net,
+                &session,
+                options.runs as usize,
+                top as usize,
+            )?)
+        }
+        None => None,
+    };
+    let config = Config::find(&model)
+        .cc(BenchError::ConfigParse)
+        .unwrap_or_default();
+    let inputs = Inputs::load(&config)?;
+    bar.set_message("Setting inputs");
+    not_terminal.then(|| eprintln!("Setting inputs"));
+    inputs.set(&net, &session, 0)?;
     let (_, _) = timeit(|| -> Result<()> {
         bar.set_message("Running session");
         not_terminal.then(|| eprintln!("Running session"));
-        net.run_session(&session).cc(BenchError)?;
+        net.run_session(&session).cc(BenchError::Inference)?;
         net.wait(&session);
         Ok(())
-    })
-    .cc(BenchError)?;
+    })?;
 
-    let mut outputs = BTreeMap::new();
-    for (name, _path) in config.outputs.iter() {
-        bar.set_message(format!("Checking output {name}"));
-        not_terminal.then(|| eprintln!("Checking output {name}"));
-        let output = unsafe {
-            net.raw_output(&session, name)
-                .cc(BenchError)?
-                .create_host_tensor_from_device(true)
-                .unchecked_host_bytes()
-                .to_vec()
-        };
-        if let Some(cd) = config.outputs.get(name) {
-            let expected = std::fs::read(&cd.path).cc(BenchError)?;
-            assert_eq!(
-                output.len(),
-                expected.len(),
-                "Failed to compare sizes of output and expected"
-            );
-            let mas = cd.data_type.mas(&output, &expected);
-            outputs.insert(name.clone(), mas);
+    let outputs = compare_outputs(&net, &session, &config, &bar)?;
+    if let Some(template) = &options.dump_outputs {
+        bar.set_message("Dumping outputs");
+        dump_outputs(&net, &session, template, &model, &sc)?;
+    }
+    let (_, samples_time) = timeit(|| -> Result<()> {
+        for sample in 0..inputs.samples {
+            bar.set_message(format!("Running sample {sample}"));
+            not_terminal.then(|| eprintln!("Running sample {sample}"));
+            inputs.set(&net, &session, sample)?;
+            net.run_session(&session).cc(BenchError::Inference)?;
+            net.wait(&session);
         }
-        drop(output);
+        Ok(())
+    })?;
+    let throughput = inputs.samples as f64 / samples_time.as_secs_f64();
+    let (input_transform, output_transform) = if options.include_io_transform {
+        bar.set_message("Timing the io transform");
+        not_terminal.then(|| eprintln!("Timing the io transform"));
+        let (input, output) = io_transform_time(&mut net, &session, &inputs)?;
+        (Some(input), Some(output))
+    } else {
+        (None, None)
+    };
+    let memory = net.memory(&session).cc(BenchError::Inference)?;
+    let flops = net.flops(&session).cc(BenchError::Inference)?;
+    let cache_size = net.cache_file_size();
+    if let Some(temp_file) = temp_file {
+        temp_file.close().cc(BenchError::Io)?;
     }
-    let memory = net.memory(&session).cc(BenchError)?;
-    let flops = net.flops(&session).cc(BenchError)?;
-    temp_file.close().cc(BenchError)?;
     Ok(Metric {
         schedule_config: sc,
         memory,
@@ -658,10 +2141,189 @@ pub fn bench(
         initial_load_time,
         cached_load_time,
         inference_time,
+        inference,
+        throughput,
+        warmup_iterations,
+        cold_start_time: None,
+        warm_cache,
+        cache_size,
+        cpu_affinity: options.applied_cpu_affinity(),
+        input_transform,
+        output_transform,
+        hottest_ops,
+        outputs,
+    })
+}
+
+/// Measure the time to first inference i.e. loading the model, creating the session and running
+/// it once with the inputs from the config
+pub fn cold_start_bench(
+    sc: ScheduleConfig,
+    model: impl AsRef<Path>,
+    bar: &indicatif::ProgressBar,
+) -> Result<Metric> {
+    let not_terminal = !std::io::stdout().is_terminal();
+    let config = Config::find(&model)
+        .cc(BenchError::ConfigParse)
+        .unwrap_or_default();
+    let inputs = Inputs::load(&config)?;
+    bar.set_message("Measuring cold start");
+    not_terminal.then(|| eprintln!("Measuring cold start"));
+    let ((net, session, initial_load_time), cold_start_time) = timeit(|| -> Result<_> {
+        let mut net = mnn::Interpreter::from_file(&model).cc(BenchError::ModelLoad)?;
+        let (session, initial_load_time) =
+            timeit(|| net.create_session(sc.clone())).cc(BenchError::SessionCreate)?;
+        inputs.set(&net, &session, 0)?;
+        net.run_session(&session).cc(BenchError::Inference)?;
+        net.wait(&session);
+        Ok((net, session, initial_load_time))
+    })?;
+    let outputs = compare_outputs(&net, &session, &config, bar)?;
+    let memory = net.memory(&session).cc(BenchError::Inference)?;
+    let flops = net.flops(&session).cc(BenchError::Inference)?;
+    Ok(Metric {
+        schedule_config: sc,
+        memory,
+        flops,
+        initial_load_time,
+        cached_load_time: Duration::ZERO,
+        inference_time: Duration::ZERO,
+        inference: InferenceStats::default(),
+        throughput: 0.0,
+        warmup_iterations: 0,
+        cold_start_time: Some(cold_start_time),
+        warm_cache: None,
+        cache_size: None,
+        cpu_affinity: None,
+        input_transform: None,
+        output_transform: None,
+        hottest_ops: None,
         outputs,
     })
 }
 
+/// Time each op over `runs` inferences with the per-op callbacks and render the total time spent
+/// in each op as a flamegraph (op type -> op name) to `path`
+///
+/// The collapsed stacks are written to `path` with a `.folded` extension
+pub fn write_flamegraph(
+    net: &mut mnn::Interpreter,
+    session: &mnn::Session,
+    path: &Path,
+    runs: usize,
+) -> Result<()> {
+    let mut times = BTreeMap::<(String, String), Duration>::new();
+    for _ in 0..runs {
+        for op in net.profile_ops(session).cc(BenchError::Inference)? {
+            // ';' separates the frames of a collapsed stack
+            let frame = |name: &str| name.replace(';', ":");
+            *times
+                .entry((frame(&op.op_type), frame(&op.name)))
+                .or_default() += op.time;
+        }
+    }
+    let stacks = times
+        .iter()
+        .map(|((op_type, name), time)| format!("{op_type};{name} {}", time.as_micros()))
+        .collect::<Vec<_>>();
+    let folded = path.with_extension("folded");
+    std::fs::write(&folded, stacks.join("\n"))
+        .cc(BenchError::Io)
+        .attach_printable_lazy(|| format!("Failed to write {}", folded.display()))?;
+    let mut options = inferno::flamegraph::Options::default();
+    options.count_name = "us".into();
+    let svg = std::fs::File::create(path)
+        .cc(BenchError::Io)
+        .attach_printable_lazy(|| format!("Failed to create {}", path.display()))?;
+    inferno::flamegraph::from_lines(
+        &mut options,
+        stacks.iter().map(String::as_str),
+        std::io::BufWriter::new(svg),
+    )
+    .cc(BenchError::Io)
+    .attach_printable_lazy(|| format!("Failed to render {}", path.display()))?;
+    Ok(())
+}
+
+/// Time each op over `runs` inferences and return the `top` ops with the highest mean time
+pub fn hottest_ops(
+    net: &mut mnn::Interpreter,
+    session: &mnn::Session,
+    runs: usize,
+    top: usize,
+) -> Result<Vec<OpTime>> {
+    let mut ops = Vec::<OpTime>::new();
+    for _ in 0..runs {
+        let profile = net.profile_ops(session).cc(BenchError::Inference)?;
+        if ops.is_empty() {
+            ops = profile
+                .into_iter()
+                .map(|op| OpTime {
+                    name: op.name,
+                    op_type: op.op_type,
+                    flops: op.flops,
+                    time: op.time,
+                })
+                .collect();
+        } else {
+            // The ops run in the same order every time
+            ops.iter_mut()
+                .zip(profile)
+                .for_each(|(total, op)| total.time += op.time);
+        }
+    }
+    ops.iter_mut().for_each(|op| op.time /= runs.max(1) as u32);
+    ops.sort_by_key(|op| std::cmp::Reverse(op.time));
+    ops.truncate(top);
+    Ok(ops)
+}
+
+/// Measure the mean time to copy the inputs of each sample to the session and the outputs of
+/// the session back to the host
+///
+/// The copies convert between the host and the backend layout / data type so for quantized
+/// models this is the quantize / dequantize overhead that `run_session` doesn't include
+pub fn io_transform_time(
+    net: &mut mnn::Interpreter,
+    session: &mnn::Session,
+    inputs: &Inputs,
+) -> Result<(Duration, Duration)> {
+    let samples = inputs.samples.max(1);
+    let mut input_time = Duration::ZERO;
+    let mut output_time = Duration::ZERO;
+    for sample in 0..samples {
+        let (_, elapsed) = timeit(|| -> Result<()> {
+            inputs.set(net, session, sample)?;
+            net.wait(session);
+            Ok(())
+        })?;
+        input_time += elapsed;
+        net.run_session(session).cc(BenchError::Inference)?;
+        net.wait(session);
+        let start = std::time::Instant::now();
+        for output in &net.outputs(session) {
+            output
+                .raw_tensor()
+                .create_host_tensor_from_device(true)
+                .destroy();
+        }
+        let elapsed = start.elapsed();
+        output_time += elapsed;
+    }
+    Ok((input_time / samples as u32, output_time / samples as u32))
+}
+
+/// Create a host tensor for the input tensor, optionally with a different dimension type
+pub fn create_host_tensor(
+    tensor: &mnn::RawTensor,
+    dim_type: Option<mnn::DimensionType>,
+) -> mnn::RawTensor<'static> {
+    match dim_type {
+        Some(dim_type) => tensor.create_host_tensor_with_dimension_type(dim_type),
+        None => tensor.create_host_tensor_from_device(false),
+    }
+}
+
 pub fn timeit<F: FnOnce() -> Result<T, E>, T, E>(f: F) -> Result<(T, Duration), E> {
     let start = std::time::Instant::now();
     let result = f()?;
@@ -669,9 +2331,200 @@ pub fn timeit<F: FnOnce() -> Result<T, E>, T, E>(f: F) -> Result<(T, Duration),
     Ok((result, duration))
 }
 
+/// The cache file of a model and config in the `--persist-cache` directory
+///
+/// The file is named after the full file name of the model and a hash of its canonical path, so
+/// models with the same name in different directories (or only differing in their extension)
+/// never share a cache.
+pub fn persisted_cache_path(
+    dir: &Path,
+    model: impl AsRef<Path>,
+    sc: &ScheduleConfig,
+) -> Result<PathBuf> {
+    let model = model.as_ref();
+    let [_, forward, power, precision, memory, threads] = config_names(model, sc)?;
+    let path = dunce::canonicalize(model)
+        .cc(BenchError::Io)
+        .attach_printable_lazy(|| format!("Failed to find {}", model.display()))?;
+    let name = path
+        .file_name()
+        .ok_or_else(|| Report::new(BenchError::ModelLoad))
+        .attach_printable_lazy(|| format!("Invalid model path {}", model.display()))?
+        .to_string_lossy();
+    // FNV-1a, unlike the std hasher it's stable across builds so the caches survive upgrades
+    let hash = path
+        .as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf29ce484222325u64, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    Ok(dir.join(format!(
+        "{name}-{hash:016x}-{forward}-{power}-{precision}-{memory}-{threads}.cache"
+    )))
+}
+
+/// The model stem, the normalized forward, power, precision and memory modes and the thread
+/// count of a config
+pub fn config_names(model: impl AsRef<Path>, sc: &ScheduleConfig) -> Result<[String; 6]> {
+    let model = model.as_ref();
+    let stem = model
+        .file_stem()
+        .ok_or_else(|| Report::new(BenchError::ModelLoad))
+        .attach_printable_lazy(|| format!("Invalid model path {}", model.display()))?;
+    let threads = sc.get_num_threads();
+    let sc = serde_json::to_value(sc).cc(BenchError::Io)?;
+    let field = |pointer: &str| {
+        sc.pointer(pointer)
+            .and_then(serde_json::Value::as_str)
+            .map(normalize_key)
+            .unwrap_or_else(|| "default".into())
+    };
+    Ok([
+        stem.to_string_lossy().into_owned(),
+        field("/type"),
+        field("/backend_config/power"),
+        field("/backend_config/precision"),
+        field("/backend_config/memory"),
+        threads.to_string(),
+    ])
+}
+
+/// A path template for `--dump-outputs` e.g. `out/{model}_{forward}_{output}.bin`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTemplate(String);
+
+impl OutputTemplate {
+    pub const PLACEHOLDERS: [&'static str; 7] = [
+        "model",
+        "forward",
+        "power",
+        "precision",
+        "memory",
+        "threads",
+        "output",
+    ];
+
+    /// Split the template into literal text and placeholder names
+    fn parts(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        let mut rest = self.0.as_str();
+        core::iter::from_fn(move || {
+            if rest.is_empty() {
+                return None;
+            }
+            match rest.split_once('{') {
+                Some((text, tail)) => {
+                    let (name, tail) = tail.split_once('}').unwrap_or((tail, ""));
+                    rest = tail;
+                    Some((text, Some(name)))
+                }
+                None => Some((core::mem::take(&mut rest), None)),
+            }
+        })
+    }
+
+    pub fn uses(&self, placeholder: &str) -> bool {
+        self.parts().any(|(_, name)| name == Some(placeholder))
+    }
+
+    /// Expand the placeholders with the values in the order of [`OutputTemplate::PLACEHOLDERS`]
+    pub fn expand(&self, values: [&str; 7]) -> PathBuf {
+        self.parts()
+            .fold(String::new(), |mut path, (text, name)| {
+                path.push_str(text);
+                if let Some(index) = name.and_then(|name| {
+                    Self::PLACEHOLDERS
+                        .iter()
+                        .position(|placeholder| *placeholder == name)
+                }) {
+                    path.push_str(values[index]);
+                }
+                path
+            })
+            .into()
+    }
+}
+
+impl core::str::FromStr for OutputTemplate {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let template = Self(s.to_string());
+        for (_, name) in template.parts() {
+            if let Some(name) = name.filter(|name| !Self::PLACEHOLDERS.contains(name)) {
+                return Err(format!(
+                    "Unknown placeholder {{{name}}} in {s}, expected one of {}",
+                    Self::PLACEHOLDERS.map(|p| format!("{{{p}}}")).join(", ")
+                ));
+            }
+        }
+        if s.matches('{').count() != s.matches('}').count() {
+            return Err(format!("Unbalanced braces in {s}"));
+        }
+        Ok(template)
+    }
+}
+
+impl core::fmt::Display for OutputTemplate {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Write every output tensor of the session to the path expanded from the template
+pub fn dump_outputs(
+    net: &mnn::Interpreter,
+    session: &mnn::Session,
+    template: &OutputTemplate,
+    model: impl AsRef<Path>,
+    sc: &ScheduleConfig,
+) -> Result<()> {
+    let [model, forward, power, precision, memory, threads] = config_names(model, sc)?;
+    let outputs = net.outputs(session);
+    if outputs.size() > 1 && !template.uses("output") {
+        return Err(Report::new(BenchError::Io).attach_printable(format!(
+            "The model has {} outputs but {template} doesn't use {{output}}",
+            outputs.size()
+        )));
+    }
+    for output in outputs.iter() {
+        let name = output
+            .name()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || "-_.".contains(c) {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        let path = template.expand([
+            &model, &forward, &power, &precision, &memory, &threads, &name,
+        ]);
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent)
+                .cc(BenchError::Io)
+                .attach_printable_lazy(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let host = output.raw_tensor().create_host_tensor_from_device(true);
+        let data = host
+            .host_bytes()
+            .cc(BenchError::Inference)
+            .map(<[u8]>::to_vec);
+        host.destroy();
+        std::fs::write(&path, data?)
+            .cc(BenchError::Io)
+            .attach_printable_lazy(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
 pub fn temp_file_path() -> Result<tempfile::TempPath> {
     Ok(tempfile::NamedTempFile::new()
-        .cc(BenchError)?
+        .cc(BenchError::Io)?
         .into_temp_path())
 }
 
@@ -689,7 +2542,7 @@ pub fn p_read(reader: impl std::io::Read + Send + Sync + 'static) -> ProgressBar
         let mut buffer = String::new();
         while reader
             .read_line(&mut buffer)
-            .cc(BenchError)
+            .cc(BenchError::Io)
             .expect("Failed to read line")
             > 0
         {
@@ -704,3 +2557,296 @@ pub fn p_read(reader: impl std::io::Read + Send + Sync + 'static) -> ProgressBar
     });
     bar_
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_config_items_threads() {
+        let items = ScheduleConfigItems {
+            forward: vec![mnn::ForwardType::CPU],
+            power: vec![mnn::PowerMode::Normal],
+            precision: vec![mnn::PrecisionMode::Normal],
+            memory: vec![mnn::MemoryMode::Normal],
+            threads: vec![1, 4],
+        };
+        assert_eq!(items.combinations(), 2);
+        let configs = items.into_iter().collect::<Vec<_>>();
+        assert_ne!(configs[0].key(), configs[1].key());
+        let threads = configs
+            .into_iter()
+            .map(|item| item.into_schedule_config().get_num_threads())
+            .collect::<Vec<_>>();
+        assert_eq!(threads, [1, 4]);
+    }
+
+    #[test]
+    fn test_schedule_config_items_threads_cpu_only() {
+        let items = ScheduleConfigItems {
+            forward: vec![mnn::ForwardType::CPU, mnn::ForwardType::Auto],
+            power: vec![mnn::PowerMode::Normal],
+            precision: vec![mnn::PrecisionMode::Normal],
+            memory: vec![mnn::MemoryMode::Normal],
+            threads: vec![1, 2, 8],
+        };
+        assert_eq!(items.combinations(), 4);
+        let configs = items.into_iter().collect::<Vec<_>>();
+        assert_eq!(configs.len(), 4);
+        let auto = configs
+            .iter()
+            .filter(|item| item.forward == mnn::ForwardType::Auto)
+            .map(|item| item.threads)
+            .collect::<Vec<_>>();
+        assert_eq!(auto, [mnn::ScheduleConfig::new().get_num_threads()]);
+    }
+
+    #[test]
+    fn test_output_template_threads() {
+        let template = "out/{model}_{threads}_{output}.bin"
+            .parse::<OutputTemplate>()
+            .unwrap();
+        assert!(template.uses("threads"));
+        assert_eq!(
+            template.expand(["m", "cpu", "normal", "normal", "normal", "4", "y"]),
+            PathBuf::from("out/m_4_y.bin")
+        );
+    }
+
+    #[test]
+    fn test_output_dir_paths() {
+        let dir = Path::new("out");
+        let models = ["a/yolo.v5.mnn", "a/yolo.v5.tflite.mnn", "b/net.mnn"].map(PathBuf::from);
+        assert_eq!(
+            output_dir_paths(dir, &models, Format::Json).unwrap(),
+            [
+                "out/yolo.v5.mnn.json",
+                "out/yolo.v5.tflite.mnn.json",
+                "out/net.mnn.json"
+            ]
+            .map(PathBuf::from)
+        );
+        let models = ["a/net.mnn", "b/net.mnn"].map(PathBuf::from);
+        assert!(output_dir_paths(dir, &models, Format::Csv).is_err());
+    }
+
+    #[test]
+    fn test_persisted_cache_path() {
+        let dir = std::env::temp_dir().join(format!("mnn-bencher-cache-{}", std::process::id()));
+        for sub in ["a", "b"] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+            std::fs::write(dir.join(sub).join("net.mnn"), b"").unwrap();
+        }
+        std::fs::write(dir.join("a").join("net.onnx.mnn"), b"").unwrap();
+        let sc = ScheduleConfig::new();
+        let paths = ["a/net.mnn", "b/net.mnn", "a/net.onnx.mnn", "b/../a/net.mnn"]
+            .map(|model| persisted_cache_path(&dir, dir.join(model), &sc).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_ne!(paths[0], paths[1]);
+        assert_ne!(paths[0], paths[2]);
+        assert_eq!(paths[0], paths[3]);
+        let name = paths[0].file_name().unwrap().to_string_lossy();
+        assert!(name.starts_with("net.mnn-"), "{name}");
+    }
+
+    #[test]
+    fn test_metrics_to_csv() {
+        let results = serde_json::json!([{
+            "Ok": {
+                "model": "/models/a,b.mnn",
+                "metrics": [{
+                    "memory": "12MiB",
+                    "flops": "345M",
+                    "initial_load_time": "20ms",
+                    "cached_load_time": "10ms",
+                    "inference_time": "5ms",
+                    "inference_min": "4.500ms",
+                    "inference_p50": "5.000ms",
+                    "inference_p95": "6.250ms",
+                    "inference_max": "7.000ms",
+                    "inference_stddev": "0.400ms",
+                    "throughput": "190.12/s",
+                    "warmup_iterations": 10,
+                    "input_transform": "0.250ms",
+                    "output_transform": "0.125ms",
+                    "schedule_config": {
+                        "type": "cpu",
+                        "num_threads": 4,
+                        "backend_config": {
+                            "power": "high",
+                            "precision": "low",
+                            "memory": "normal"
+                        }
+                    }
+                }]
+            }
+        }]);
+        let csv = metrics_to_csv(&results);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(CSV_COLUMNS.join(",").as_str()));
+        assert_eq!(
+            lines.next(),
+            Some(
+                "\"/models/a,b.mnn\",cpu,high,low,normal,4,12,345,20,10,,5,4.500,5.000,6.250,7.000,0.400,190.12,10,0.250,0.125"
+            )
+        );
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn test_compare_results() {
+        let metric = |inference: &str, memory: &str, precision: &str| {
+            serde_json::json!({
+                "memory": memory,
+                "flops": "345M",
+                "inference_p50": inference,
+                "input_transform": "0.250ms",
+                "schedule_config": {
+                    "type": "cpu",
+                    "num_threads": 4,
+                    "backend_config": {
+                        "power": "normal",
+                        "precision": precision,
+                        "memory": "normal"
+                    }
+                }
+            })
+        };
+        let baseline = serde_json::json!({
+            "metadata": {},
+            "results": [{
+                "Ok": {
+                    "model": "/models/a.mnn",
+                    "metrics": [
+                        metric("10.000ms", "100MiB", "low"),
+                        metric("0.400ms", "100MiB", "high"),
+                        metric("0.000ms", "100MiB", "normal")
+                    ]
+                }
+            }]
+        });
+        // The current run as the lines of a jsonl results file
+        let current = serde_json::json!([
+            { "model": "/models/a.mnn", "metric": metric("12.000ms", "101MiB", "low") },
+            { "model": "/models/a.mnn", "metric": metric("0.300ms", "100MiB", "high") },
+            { "model": "/models/a.mnn", "metric": metric("0.100ms", "100MiB", "normal") },
+            { "model": "/models/b.mnn", "metric": metric("1.000ms", "1MiB", "low") },
+        ]);
+
+        let comparisons = compare_results(&baseline, &current);
+        assert_eq!(comparisons.len(), 3);
+        assert_eq!(comparisons[0].config[2], "low");
+        assert_eq!(
+            comparisons[0].values,
+            [
+                Some((10.0, 12.0)),
+                Some((100.0, 101.0)),
+                Some((345.0, 345.0)),
+                Some((0.25, 0.25)),
+                None
+            ]
+        );
+        assert_eq!(
+            comparisons[0].deltas(),
+            [Some(20.0), Some(1.0), Some(0.0), Some(0.0), None]
+        );
+        assert_eq!(comparisons[0].regressions(5.0), ["inference_p50"]);
+        assert_eq!(comparisons[0].regressions(0.5), ["inference_p50", "memory"]);
+        assert_eq!(comparisons[1].values[0], Some((0.4, 0.3)));
+        assert!(comparisons[1].regressions(0.0).is_empty());
+        // A zero baseline has no relative change
+        assert_eq!(comparisons[2].values[0], None);
+        assert!(comparisons[2].regressions(0.0).is_empty());
+
+        let table = render_comparisons(&comparisons, 5.0);
+        let mut lines = table.lines();
+        assert_eq!(
+            lines.next(),
+            Some("model\tconfig\tinference_p50\tmemory\tflops\tinput_transform\toutput_transform")
+        );
+        assert!(lines.next().unwrap().contains("10 -> 12 (+20.0%) !"));
+        assert!(!lines.next().unwrap().contains('!'));
+        assert!(lines.next().unwrap().contains("normal/normal/4\t-\t"));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn test_completed_results() {
+        let metric = |threads: i32| {
+            serde_json::json!({
+                "inference_time": "5ms",
+                "schedule_config": {
+                    "type": "CPU",
+                    "num_threads": threads,
+                    "backend_config": { "power": "High" }
+                }
+            })
+        };
+        let metrics = Metrics {
+            model: "/models/a.mnn".into(),
+            model_metadata: BTreeMap::new(),
+            metrics: vec![metric(1)],
+        };
+        let lines = [
+            serde_json::to_string(&metrics).unwrap(),
+            // A line written by older versions
+            serde_json::json!({ "model": "/models/a.mnn", "threads": 2, "metric": metric(2) })
+                .to_string(),
+            r#"{"model": "/models/a.mnn", "metri"#.to_owned(),
+        ];
+        let path = std::env::temp_dir().join(format!(
+            "mnn-bencher-completed-{}.jsonl",
+            std::process::id()
+        ));
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let done = completed_results(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let key = |threads: &str| {
+            (
+                PathBuf::from("/models/a.mnn"),
+                ["cpu", "high", "normal", "normal", threads].map(String::from),
+            )
+        };
+        assert_eq!(done, [key("1"), key("2")].into_iter().collect());
+    }
+
+    #[test]
+    fn test_merge_metrics() {
+        let metrics = |model: &str| -> Result<Metrics> {
+            Ok(Metrics {
+                model: model.into(),
+                model_metadata: BTreeMap::new(),
+                metrics: vec![serde_json::Value::Null],
+            })
+        };
+        let merged = Metrics::merge(vec![
+            metrics("a.mnn"),
+            metrics("a.mnn"),
+            Err(Report::new(BenchError::Inference)),
+            metrics("a.mnn"),
+            metrics("b.mnn"),
+        ]);
+        let lens = merged
+            .iter()
+            .map(|result| result.as_ref().map_or(0, |metrics| metrics.metrics.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(lens, [2, 0, 1, 1]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_wait_timeout_kills_hung_child() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let start = std::time::Instant::now();
+        let status = wait_timeout(&mut child, Some(Duration::from_millis(200))).unwrap();
+        assert!(status.is_none());
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let status = wait_timeout(&mut child, Some(Duration::from_secs(10))).unwrap();
+        assert!(status.is_some_and(|status| status.success()));
+    }
+}